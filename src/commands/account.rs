@@ -0,0 +1,301 @@
+//! Account queries.
+
+use cosmrs::proto::cosmos::bank::v1beta1::query_client::QueryClient as BankQueryClient;
+use cosmrs::proto::cosmos::bank::v1beta1::QueryAllBalancesRequest;
+use cosmrs::proto::cosmos::distribution::v1beta1::query_client::QueryClient as DistributionQueryClient;
+use cosmrs::proto::cosmos::distribution::v1beta1::QueryDelegationTotalRewardsRequest;
+use cosmrs::proto::cosmos::staking::v1beta1::query_client::QueryClient as StakingQueryClient;
+use cosmrs::proto::cosmos::staking::v1beta1::{
+    QueryDelegatorDelegationsRequest, QueryDelegatorUnbondingDelegationsRequest, QueryParamsRequest,
+};
+use cosmrs::proto::cosmos::tx::v1beta1::{GetTxsEventRequest, OrderBy};
+use serde_json::Value;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::commands::tx::{tx_record, FEE_DENOM};
+use crate::utils::grpc_endpoint;
+use crate::{connect_to_gevulot, print_object, ChainArgs, OutputArgs};
+
+/// Number of decimal places of Cosmos SDK decimal amounts.
+const DEC_PRECISION: usize = 18;
+
+/// Account command.
+#[derive(Clone, Debug, clap::Parser)]
+pub struct Command {
+    #[command(flatten)]
+    chain_args: ChainArgs,
+
+    #[command(subcommand)]
+    subcommand: Subcommand,
+}
+
+impl Command {
+    /// Match account subcommand and run it.
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let chain_args = &self.chain_args.resolve()?;
+        let value = match &self.subcommand {
+            Subcommand::History {
+                address,
+                received,
+                page,
+                limit,
+                oldest_first,
+            } => {
+                account_history(chain_args, address, *received, *page, *limit, *oldest_first).await
+            }
+        }?;
+        print_object(output, &value)
+    }
+}
+
+/// Account subcommand.
+#[derive(Clone, Debug, clap::Subcommand)]
+enum Subcommand {
+    /// List transactions sent by the account with their fees and decoded messages.
+    ///
+    /// Newest transactions are listed first.
+    History {
+        /// The address of the account.
+        address: String,
+
+        /// List transfers received by the account instead of sent transactions.
+        #[arg(long)]
+        received: bool,
+
+        /// Page number to list, starting from 1.
+        #[arg(long, value_name = "NUM", default_value_t = 1)]
+        page: u64,
+
+        /// Maximum number of transactions to list.
+        #[arg(long, value_name = "NUM", default_value_t = 50)]
+        limit: u64,
+
+        /// List oldest transactions first.
+        #[arg(long)]
+        oldest_first: bool,
+    },
+}
+
+/// Lists transactions of the account.
+async fn account_history(
+    chain_args: &ChainArgs,
+    address: &str,
+    received: bool,
+    page: u64,
+    limit: u64,
+    oldest_first: bool,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = connect_to_gevulot(chain_args).await?;
+    let order_by = if oldest_first {
+        OrderBy::Asc
+    } else {
+        OrderBy::Desc
+    };
+    let request = GetTxsEventRequest {
+        query: history_query(address, received),
+        order_by: order_by as i32,
+        page,
+        limit,
+        ..Default::default()
+    };
+    let response = client
+        .base_client
+        .write()
+        .await
+        .tx_client
+        .get_txs_event(request)
+        .await
+        .map_err(|status| status.message().to_string())?
+        .into_inner();
+    let records = response
+        .tx_responses
+        .iter()
+        .enumerate()
+        .map(|(i, tx_response)| tx_record(response.txs.get(i), tx_response))
+        .collect::<Vec<_>>();
+    Ok(serde_json::json!(records))
+}
+
+/// Returns the event query matching transactions of the account.
+fn history_query(address: &str, received: bool) -> String {
+    if received {
+        format!("transfer.recipient='{}'", address)
+    } else {
+        format!("message.sender='{}'", address)
+    }
+}
+
+/// Retrieves the account with its balances in all denominations, delegations,
+/// pending rewards and unbonding delegations.
+///
+/// If `denom` is set, only amounts in this denomination are included,
+/// otherwise `balance` is in the fee denomination.
+pub async fn account_info(
+    chain_args: &ChainArgs,
+    address: &str,
+    denom: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let chain_args = chain_args.resolve()?;
+    let client = connect_to_gevulot(&chain_args).await?;
+    let account = client
+        .base_client
+        .write()
+        .await
+        .get_account(address)
+        .await?;
+    let endpoint = grpc_endpoint(&chain_args);
+    let matches = |coin_denom: &str| denom.is_none_or(|denom| denom == coin_denom);
+
+    let mut bank = BankQueryClient::connect(endpoint.clone()).await?;
+    let balances = bank
+        .all_balances(QueryAllBalancesRequest {
+            address: address.to_string(),
+            ..Default::default()
+        })
+        .await?
+        .into_inner()
+        .balances;
+    let balance_denom = denom.unwrap_or(FEE_DENOM);
+    let balance = balances
+        .iter()
+        .find(|coin| coin.denom == balance_denom)
+        .map(|coin| coin.amount.clone())
+        .unwrap_or("0".to_string());
+    let balances = balances
+        .into_iter()
+        .filter(|coin| matches(&coin.denom))
+        .map(|coin| serde_json::json!({ "denom": coin.denom, "amount": coin.amount }))
+        .collect::<Vec<_>>();
+
+    let mut staking = StakingQueryClient::connect(endpoint.clone()).await?;
+    let bond_denom = staking
+        .params(QueryParamsRequest {})
+        .await?
+        .into_inner()
+        .params
+        .map(|params| params.bond_denom)
+        .unwrap_or_default();
+    let delegations = staking
+        .delegator_delegations(QueryDelegatorDelegationsRequest {
+            delegator_addr: address.to_string(),
+            pagination: None,
+        })
+        .await?
+        .into_inner()
+        .delegation_responses
+        .into_iter()
+        .filter_map(|response| {
+            let delegation = response.delegation?;
+            let balance = response.balance?;
+            matches(&balance.denom).then(|| {
+                serde_json::json!({
+                    "validator": delegation.validator_address,
+                    "shares": format_dec(&delegation.shares),
+                    "denom": balance.denom,
+                    "amount": balance.amount,
+                })
+            })
+        })
+        .collect::<Vec<_>>();
+    let unbonding = if matches(&bond_denom) {
+        staking
+            .delegator_unbonding_delegations(QueryDelegatorUnbondingDelegationsRequest {
+                delegator_addr: address.to_string(),
+                pagination: None,
+            })
+            .await?
+            .into_inner()
+            .unbonding_responses
+            .into_iter()
+            .flat_map(|unbonding| {
+                let validator = unbonding.validator_address;
+                let bond_denom = bond_denom.clone();
+                unbonding.entries.into_iter().map(move |entry| {
+                    serde_json::json!({
+                        "validator": validator,
+                        "creation_height": entry.creation_height,
+                        "completion_time": entry
+                            .completion_time
+                            .map(|time| format_timestamp(time.seconds)),
+                        "denom": bond_denom,
+                        "initial_balance": entry.initial_balance,
+                        "balance": entry.balance,
+                    })
+                })
+            })
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+
+    let mut distribution = DistributionQueryClient::connect(endpoint).await?;
+    let rewards = distribution
+        .delegation_total_rewards(QueryDelegationTotalRewardsRequest {
+            delegator_address: address.to_string(),
+        })
+        .await?
+        .into_inner()
+        .rewards
+        .into_iter()
+        .flat_map(|reward| {
+            let validator = reward.validator_address;
+            reward.reward.into_iter().map(move |coin| {
+                serde_json::json!({
+                    "validator": validator,
+                    "denom": coin.denom,
+                    "amount": format_dec(&coin.amount),
+                })
+            })
+        })
+        .filter(|reward| matches(reward["denom"].as_str().unwrap_or_default()))
+        .collect::<Vec<_>>();
+
+    Ok(serde_json::json!({
+        "account_number": account.account_number,
+        "sequence": account.sequence,
+        "balance": balance,
+        "balance_denom": balance_denom,
+        "balances": balances,
+        "delegations": delegations,
+        "rewards": rewards,
+        "unbonding": unbonding,
+    }))
+}
+
+/// Formats UNIX timestamp in seconds as RFC 3339 string.
+fn format_timestamp(seconds: i64) -> String {
+    let time = UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64);
+    humantime::format_rfc3339_seconds(time).to_string()
+}
+
+/// Formats Cosmos SDK decimal amount, which is returned as an integer
+/// scaled by 10^18, with a decimal point. Trailing zeros are removed.
+fn format_dec(amount: &str) -> String {
+    if amount.contains('.') || !amount.bytes().all(|b| b.is_ascii_digit()) {
+        return amount.to_string();
+    }
+    let padded = format!("{:0>width$}", amount, width = DEC_PRECISION + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - DEC_PRECISION);
+    let integer = integer.trim_start_matches('0');
+    let integer = if integer.is_empty() { "0" } else { integer };
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_dec() {
+        assert_eq!(format_dec("1500000000000000000000"), "1500");
+        assert_eq!(format_dec("1234500000000000000"), "1.2345");
+        assert_eq!(format_dec("5"), "0.000000000000000005");
+        assert_eq!(format_dec("0"), "0");
+        assert_eq!(format_dec("1.5"), "1.5");
+    }
+}
@@ -0,0 +1,233 @@
+//! Applying and deleting objects described in files.
+
+use gevulot_rs::builders::{MsgDeletePinBuilder, MsgDeleteWorkerBuilder};
+use gevulot_rs::proto::gevulot::gevulot::{
+    MsgCreatePinResponse, MsgCreateTaskResponse, MsgCreateWorkerResponse, MsgDeleteTask,
+};
+use patharg::InputArg;
+use serde_yaml::Value as YamlValue;
+
+use crate::commands::batch::{self, BatchArgs, BatchMessage};
+use crate::commands::tx::signer_address;
+use crate::commands::{pins, tasks, workers};
+use crate::template::TemplateArgs;
+use crate::utils::Document;
+use crate::{connect_to_gevulot, print_object, ChainArgs, OutputArgs};
+
+/// Apply arguments.
+#[derive(Clone, Debug, clap::Parser)]
+pub struct ApplyArgs {
+    #[command(flatten)]
+    chain_args: ChainArgs,
+
+    /// The file or directory to read documents from or '-' to read from stdin.
+    ///
+    /// Every document is created according to its `kind`.
+    /// Documents with `metadata.id` update existing objects.
+    #[arg(short, long, default_value_t)]
+    file: InputArg,
+
+    #[command(flatten)]
+    batch_args: BatchArgs,
+
+    #[command(flatten)]
+    template_args: TemplateArgs,
+}
+
+impl ApplyArgs {
+    /// Create or update all objects described in the file.
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let value = submit(
+            &self.chain_args.unlock()?,
+            &self.file,
+            &self.batch_args,
+            &self.template_args,
+            apply_message,
+        )
+        .await?;
+        print_object(output, &value)
+    }
+}
+
+/// Delete arguments.
+#[derive(Clone, Debug, clap::Parser)]
+pub struct DeleteArgs {
+    #[command(flatten)]
+    chain_args: ChainArgs,
+
+    /// The file or directory to read documents from or '-' to read from stdin.
+    ///
+    /// Tasks and workers are identified by `metadata.id`, pins by `spec.cid`.
+    #[arg(short, long, default_value_t)]
+    file: InputArg,
+
+    #[command(flatten)]
+    batch_args: BatchArgs,
+
+    #[command(flatten)]
+    template_args: TemplateArgs,
+}
+
+impl DeleteArgs {
+    /// Delete all objects described in the file.
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let value = submit(
+            &self.chain_args.unlock()?,
+            &self.file,
+            &self.batch_args,
+            &self.template_args,
+            delete_message,
+        )
+        .await?;
+        print_object(output, &value)
+    }
+}
+
+/// Reads documents and submits messages built from them in batches.
+async fn submit(
+    chain_args: &ChainArgs,
+    file: &InputArg,
+    batch_args: &BatchArgs,
+    template_args: &TemplateArgs,
+    build: fn(&YamlValue, String) -> Result<BatchMessage, Box<dyn std::error::Error>>,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let documents = template_args
+        .read_documents(file.path_ref().map(|v| &**v))
+        .await?;
+    if documents.is_empty() {
+        return Err("no documents found".into());
+    }
+    let client = connect_to_gevulot(chain_args).await?;
+    let me = signer_address(chain_args, &client).await?;
+    let entries = documents
+        .iter()
+        .map(|document| {
+            let mut record = batch::document_record(document);
+            let msg = parse(document).and_then(|value| {
+                record["kind"] = value["kind"].as_str().unwrap_or_default().into();
+                build(&value, me.clone())
+            });
+            (record, msg.map_err(|err| err.to_string()))
+        })
+        .collect();
+    batch::submit_all(chain_args, &client, entries, batch_args).await
+}
+
+/// Parses the document and checks its kind and version.
+fn parse(document: &Document) -> Result<YamlValue, Box<dyn std::error::Error>> {
+    let value: YamlValue = serde_yaml::from_str(&document.content)?;
+    if value["kind"].as_str().is_none() {
+        return Err("missing kind".into());
+    }
+    match value["version"].as_str() {
+        Some("v0") => Ok(value),
+        Some(version) => Err(format!("unsupported version '{}'", version).into()),
+        None => Err("missing version".into()),
+    }
+}
+
+/// Returns `metadata.id` of the document if it is set.
+fn metadata_id(value: &YamlValue) -> Option<String> {
+    value["metadata"]["id"]
+        .as_str()
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+}
+
+/// Builds the message creating or updating the object described by the document.
+fn apply_message(
+    value: &YamlValue,
+    me: String,
+) -> Result<BatchMessage, Box<dyn std::error::Error>> {
+    let id = metadata_id(value);
+    match (value["kind"].as_str().unwrap_or_default(), id) {
+        ("Task", None) => {
+            let msg = tasks::create_task_msg(serde_yaml::from_value(value.clone())?, me)?;
+            BatchMessage::create(&msg, |resp: MsgCreateTaskResponse| resp.id)
+        }
+        ("Task", Some(_)) => {
+            Err("tasks can't be updated, remove metadata.id to create a new task".into())
+        }
+        ("Worker", None) => {
+            let msg = workers::create_worker_msg(serde_yaml::from_value(value.clone())?, me)?;
+            BatchMessage::create(&msg, |resp: MsgCreateWorkerResponse| resp.id)
+        }
+        ("Worker", Some(id)) => {
+            let msg = workers::update_worker_msg(serde_yaml::from_value(value.clone())?, me)?;
+            BatchMessage::with_id(&msg, "update", id)
+        }
+        ("Pin", None) => {
+            let msg = pins::create_pin_msg(serde_yaml::from_value(value.clone())?, me)?;
+            BatchMessage::create(&msg, |resp: MsgCreatePinResponse| resp.id)
+        }
+        ("Pin", Some(_)) => {
+            Err("pins can't be updated, remove metadata.id to create a new pin".into())
+        }
+        (kind, _) => Err(format!("unsupported kind '{}'", kind).into()),
+    }
+}
+
+/// Builds the message deleting the object described by the document.
+fn delete_message(
+    value: &YamlValue,
+    me: String,
+) -> Result<BatchMessage, Box<dyn std::error::Error>> {
+    match value["kind"].as_str().unwrap_or_default() {
+        "Task" => {
+            let id = metadata_id(value).ok_or("metadata.id is required to delete a task")?;
+            let msg = MsgDeleteTask {
+                creator: me,
+                id: id.clone(),
+            };
+            BatchMessage::with_id(&msg, "delete", id)
+        }
+        "Worker" => {
+            let id = metadata_id(value).ok_or("metadata.id is required to delete a worker")?;
+            let msg = MsgDeleteWorkerBuilder::default()
+                .creator(me)
+                .id(id.clone())
+                .into_message()?;
+            BatchMessage::with_id(&msg, "delete", id)
+        }
+        "Pin" => {
+            let cid = value["spec"]["cid"]
+                .as_str()
+                .ok_or("spec.cid is required to delete a pin")?
+                .to_string();
+            let msg = MsgDeletePinBuilder::default()
+                .creator(me)
+                .cid(cid.clone())
+                .into_message()?;
+            BatchMessage::with_id(&msg, "delete", cid)
+        }
+        kind => Err(format!("unsupported kind '{}'", kind).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(content: &str) -> Document {
+        Document {
+            file: "file.yaml".to_string(),
+            line: 1,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let value = parse(&document(
+            "kind: Worker\nversion: v0\nmetadata:\n  id: abc\n",
+        ))
+        .unwrap();
+        assert_eq!(metadata_id(&value).as_deref(), Some("abc"));
+
+        let value = parse(&document("kind: Task\nversion: v0\nmetadata:\n  id: ''\n")).unwrap();
+        assert_eq!(metadata_id(&value), None);
+
+        assert!(parse(&document("version: v0\n")).is_err());
+        assert!(parse(&document("kind: Task\nversion: v1\n")).is_err());
+    }
+}
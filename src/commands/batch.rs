@@ -0,0 +1,187 @@
+//! Batch submission of multiple documents.
+
+use cosmrs::Any;
+use gevulot_rs::GevulotClient;
+use prost::{Message, Name};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fmt;
+
+use crate::commands::tx::{self, signer_address};
+use crate::utils::Document;
+use crate::ChainArgs;
+
+/// Arguments controlling batch submission.
+#[derive(Clone, Debug, clap::Args)]
+pub struct BatchArgs {
+    /// Maximum number of messages in a single transaction
+    /// when multiple documents are submitted.
+    #[arg(long, value_name = "NUM", default_value_t = 50)]
+    pub batch_size: usize,
+}
+
+/// Message built from a document.
+pub struct BatchMessage {
+    any: Any,
+    decoded: String,
+
+    /// Action reported in the result of the document.
+    action: &'static str,
+
+    /// Extracts the ID of the affected object from the message response.
+    id: Box<dyn Fn(&[u8]) -> Result<String, String>>,
+}
+
+impl BatchMessage {
+    /// Message creating an object. Its ID is taken from the response of type `R`.
+    pub fn create<M, R>(msg: &M, id: fn(R) -> String) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        M: Message + Name + fmt::Debug,
+        R: Message + Default + 'static,
+    {
+        Ok(Self {
+            any: Any::from_msg(msg)?,
+            decoded: format!("{:#?}", msg),
+            action: "create",
+            id: Box::new(move |response| {
+                R::decode(response).map(id).map_err(|err| err.to_string())
+            }),
+        })
+    }
+
+    /// Message changing an object with known ID.
+    pub fn with_id<M>(
+        msg: &M,
+        action: &'static str,
+        id: String,
+    ) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        M: Message + Name + fmt::Debug,
+    {
+        Ok(Self {
+            any: Any::from_msg(msg)?,
+            decoded: format!("{:#?}", msg),
+            action,
+            id: Box::new(move |_| Ok(id.clone())),
+        })
+    }
+}
+
+/// Returns the description of the document reported with its result.
+pub fn document_record(document: &Document) -> Value {
+    serde_json::json!({
+        "file": document.file,
+        "line": document.line,
+    })
+}
+
+/// Creates objects from all documents, batching messages into transactions.
+///
+/// `build` creates a message from the parsed document and the creator address,
+/// `id` extracts the ID of the created object from the message response.
+pub async fn create_all<T, M, R>(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    documents: Vec<Document>,
+    batch_args: &BatchArgs,
+    build: impl Fn(T, String) -> Result<M, Box<dyn std::error::Error>>,
+    id: fn(R) -> String,
+) -> Result<Value, Box<dyn std::error::Error>>
+where
+    T: DeserializeOwned,
+    M: Message + Name + fmt::Debug,
+    R: Message + Default + 'static,
+{
+    let me = signer_address(chain_args, client).await?;
+    let entries = documents
+        .iter()
+        .map(|document| {
+            let msg = serde_yaml::from_str::<T>(&document.content)
+                .map_err(Box::<dyn std::error::Error>::from)
+                .and_then(|parsed| build(parsed, me.clone()))
+                .and_then(|msg| BatchMessage::create(&msg, id));
+            (
+                document_record(document),
+                msg.map_err(|err| err.to_string()),
+            )
+        })
+        .collect();
+    submit_all(chain_args, client, entries, batch_args).await
+}
+
+/// Submits messages built from documents in batches.
+///
+/// Every entry is the description of the document and its message or the error
+/// of building it. Every document is reported with its own status, so one invalid
+/// document doesn't prevent submission of the others.
+/// With `--dry-run` or `--generate-only` all documents must be valid.
+pub async fn submit_all(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    entries: Vec<(Value, Result<BatchMessage, String>)>,
+    batch_args: &BatchArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    if !chain_args.signs_transactions() {
+        let invalid = entries
+            .iter()
+            .filter_map(|(record, msg)| {
+                msg.as_ref().err().map(|err| {
+                    let file = record["file"].as_str().unwrap_or("-");
+                    format!("{}:{}: {}", file, record["line"], err)
+                })
+            })
+            .collect::<Vec<_>>();
+        if !invalid.is_empty() {
+            return Err(format!("invalid documents:\n{}", invalid.join("\n")).into());
+        }
+        let (anys, decoded): (Vec<_>, Vec<_>) = entries
+            .into_iter()
+            .filter_map(|(_, msg)| msg.ok())
+            .map(|msg| (msg.any, msg.decoded))
+            .unzip();
+        if chain_args.dry_run {
+            return tx::dry_run(chain_args, client, anys, decoded, "").await;
+        }
+        let txs = tx::generate_batches(chain_args, client, anys, batch_args.batch_size).await?;
+        return Ok(serde_json::to_value(txs)?);
+    }
+
+    let anys = entries
+        .iter()
+        .filter_map(|(_, msg)| msg.as_ref().ok())
+        .map(|msg| msg.any.clone())
+        .collect();
+    let mut responses = tx::send_batches(chain_args, client, anys, batch_args.batch_size)
+        .await?
+        .into_iter();
+
+    let mut results = vec![];
+    let mut failed = 0;
+    for (mut record, msg) in entries {
+        let result = msg.and_then(|msg| {
+            record["action"] = msg.action.into();
+            responses
+                .next()
+                .ok_or_else(|| "missing message response".to_string())
+                .and_then(|response| response)
+                .and_then(|response| (msg.id)(&response.value))
+        });
+        match result {
+            Ok(id) => {
+                record["status"] = "success".into();
+                record["id"] = id.into();
+            }
+            Err(err) => {
+                failed += 1;
+                record["status"] = "error".into();
+                record["error"] = err.into();
+            }
+        }
+        results.push(record);
+    }
+
+    if failed > 0 {
+        eprintln!("{} of {} documents failed", failed, results.len());
+    }
+    Ok(serde_json::json!(results))
+}
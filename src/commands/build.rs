@@ -4,7 +4,7 @@ use std::fmt;
 use std::path::PathBuf;
 
 use crate::builders::linux_vm;
-use crate::{print_object, OutputFormat};
+use crate::{print_object, OutputArgs};
 
 /// Build command.
 #[derive(Clone, Debug, clap::Parser)]
@@ -289,9 +289,9 @@ impl fmt::Display for RootFsType {
 
 impl BuildArgs {
     /// Run build subcommand.
-    pub async fn run(&self, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
         let value = build(self).await?;
-        print_object(format, &value)
+        print_object(output, &value)
     }
 }
 
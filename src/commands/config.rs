@@ -0,0 +1,132 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::config::{Config, Profile};
+use crate::{print_object, OutputArgs};
+
+/// Config command.
+#[derive(Clone, Debug, clap::Parser)]
+pub struct Command {
+    #[command(subcommand)]
+    subcommand: Subcommand,
+}
+
+impl Command {
+    /// Match config subcommand and run it.
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let value = match &self.subcommand {
+            Subcommand::Get { profile, key } => get_setting(profile.as_deref(), key),
+            Subcommand::Set {
+                profile,
+                key,
+                value,
+            } => set_setting(profile.as_deref(), key, value.as_deref()),
+            Subcommand::Use { profile } => use_profile(profile),
+            Subcommand::List => list_profiles(),
+        }?;
+        print_object(output, &value)
+    }
+}
+
+/// Config subcommand.
+#[derive(Clone, Debug, clap::Subcommand)]
+enum Subcommand {
+    /// Get a setting of the profile.
+    Get {
+        /// The profile to read from. Defaults to the current profile.
+        #[arg(long, env = "GEVULOT_PROFILE", value_name = "NAME")]
+        profile: Option<String>,
+
+        /// The name of the setting.
+        #[arg(value_parser = clap::builder::PossibleValuesParser::new(Profile::KEYS))]
+        key: String,
+    },
+
+    /// Set a setting of the profile. The profile is created if it doesn't exist.
+    Set {
+        /// The profile to modify. Defaults to the current profile.
+        #[arg(long, env = "GEVULOT_PROFILE", value_name = "NAME")]
+        profile: Option<String>,
+
+        /// The name of the setting.
+        #[arg(value_parser = clap::builder::PossibleValuesParser::new(Profile::KEYS))]
+        key: String,
+
+        /// The value of the setting. If omitted, the setting is removed.
+        value: Option<String>,
+    },
+
+    /// Set the current profile.
+    Use {
+        /// The name of the profile.
+        profile: String,
+    },
+
+    /// List all profiles. Secrets are hidden.
+    List,
+}
+
+/// Retrieves a setting of the profile.
+fn get_setting(profile: Option<&str>, key: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let config = Config::load()?;
+    let value = config
+        .profile(profile)?
+        .map(|profile| profile.get(key))
+        .transpose()?
+        .flatten();
+    Ok(serde_json::json!({ key: value }))
+}
+
+/// Updates a setting of the profile and saves the configuration.
+fn set_setting(
+    profile: Option<&str>,
+    key: &str,
+    value: Option<&str>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut config = Config::load()?;
+    let profile_name = config.profile_name(profile).to_string();
+    config
+        .profiles
+        .entry(profile_name.clone())
+        .or_default()
+        .set(key, value)?;
+    config.save()?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("Setting '{}' of profile '{}' updated successfully", key, profile_name)
+    }))
+}
+
+/// Sets the current profile and saves the configuration.
+fn use_profile(profile: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut config = Config::load()?;
+    if !config.profiles.contains_key(profile) {
+        return Err(format!(
+            "profile '{}' not found in {}",
+            profile,
+            Config::path().display()
+        )
+        .into());
+    }
+    config.current_profile = Some(profile.to_string());
+    config.save()?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("Switched to profile '{}'", profile)
+    }))
+}
+
+/// Lists all profiles with secrets hidden.
+fn list_profiles() -> Result<Value, Box<dyn std::error::Error>> {
+    let config = Config::load()?;
+    let profiles = config
+        .profiles
+        .iter()
+        .map(|(name, profile)| (name.clone(), profile.masked()))
+        .collect::<BTreeMap<_, _>>();
+    Ok(serde_json::json!({
+        "path": Config::path(),
+        "current_profile": config.profile_name(None),
+        "profiles": profiles,
+    }))
+}
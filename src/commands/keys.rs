@@ -0,0 +1,334 @@
+use bip32::Mnemonic;
+use rand_core::OsRng;
+use serde_json::Value;
+
+use crate::keystore::{hd_path, read_new_passphrase, KeySecret, Keystore, StoredKey};
+use crate::utils::{confirm, read_secret};
+use crate::{print_object, OutputArgs};
+
+/// Keys command.
+#[derive(Clone, Debug, clap::Parser)]
+pub struct Command {
+    #[command(subcommand)]
+    subcommand: Subcommand,
+}
+
+impl Command {
+    /// Match keys subcommand and run it.
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let value = match &self.subcommand {
+            Subcommand::Add {
+                name,
+                password,
+                account_prefix,
+            } => add_key(name, password, account_prefix),
+            Subcommand::Import {
+                name,
+                mnemonic,
+                private_key,
+                password,
+                account_prefix,
+            } => import_key(
+                name,
+                mnemonic.as_deref(),
+                private_key.as_deref(),
+                password,
+                account_prefix,
+            ),
+            Subcommand::List => list_keys(),
+            Subcommand::Show { name } => show_key(name),
+            Subcommand::Delete { name, yes } => delete_key(name, *yes),
+            Subcommand::Export { name } => export_key(name),
+            Subcommand::Derive {
+                name,
+                mnemonic,
+                password,
+                account_prefix,
+                count,
+                account,
+                start,
+            } => derive_keys(
+                name.as_deref(),
+                mnemonic.as_deref(),
+                password,
+                account_prefix,
+                *count,
+                *account,
+                *start,
+            ),
+        }?;
+        print_object(output, &value)
+    }
+}
+
+/// Keys subcommand.
+#[derive(Clone, Debug, clap::Subcommand)]
+enum Subcommand {
+    /// Generate a new key and store it encrypted in the keystore.
+    ///
+    /// The mnemonic is printed once, write it down to be able to recover the key.
+    Add {
+        /// The name of the key.
+        name: String,
+
+        /// The BIP-39 password for the mnemonic.
+        #[arg(short, long, default_value_t, hide_default_value = true)]
+        password: String,
+
+        /// The account prefix to use for the key.
+        #[arg(short, long, default_value_t = String::from("gvlt"))]
+        account_prefix: String,
+    },
+
+    /// Import an existing mnemonic or private key into the keystore.
+    ///
+    /// If neither mnemonic nor private key is provided, the mnemonic is prompted.
+    /// The private key takes precedence over the mnemonic.
+    Import {
+        /// The name of the key.
+        name: String,
+
+        /// The mnemonic to import.
+        #[arg(long, env = "GEVULOT_MNEMONIC", hide_env_values = true)]
+        mnemonic: Option<String>,
+
+        /// The hex-encoded private key to import.
+        #[arg(long, env = "GEVULOT_PRIVATE_KEY", hide_env_values = true)]
+        private_key: Option<String>,
+
+        /// The BIP-39 password for the mnemonic.
+        #[arg(short, long, default_value_t, hide_default_value = true)]
+        password: String,
+
+        /// The account prefix to use for the key.
+        #[arg(short, long, default_value_t = String::from("gvlt"))]
+        account_prefix: String,
+    },
+
+    /// List all keys in the keystore.
+    List,
+
+    /// Show the address and public key of a key.
+    Show {
+        /// The name of the key.
+        name: String,
+    },
+
+    /// Delete a key from the keystore.
+    Delete {
+        /// The name of the key.
+        name: String,
+
+        /// Do not ask for confirmation.
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Unlock a key and print its secret (mnemonic or private key).
+    Export {
+        /// The name of the key.
+        name: String,
+    },
+
+    /// List addresses derived from a mnemonic at consecutive address indices.
+    ///
+    /// The mnemonic is taken from the keystore key, --mnemonic or prompted.
+    /// Addresses use the derivation path m/44'/118'/ACCOUNT'/0/INDEX.
+    Derive {
+        /// The name of the keystore key holding the mnemonic. Takes precedence over --mnemonic.
+        name: Option<String>,
+
+        /// The mnemonic to derive addresses from.
+        #[arg(long, env = "GEVULOT_MNEMONIC", hide_env_values = true)]
+        mnemonic: Option<String>,
+
+        /// The BIP-39 password for the mnemonic.
+        #[arg(short, long, default_value_t, hide_default_value = true)]
+        password: String,
+
+        /// The account prefix to use for the addresses.
+        #[arg(short, long, default_value_t = String::from("gvlt"))]
+        account_prefix: String,
+
+        /// Number of addresses to list.
+        #[arg(long, value_name = "NUM", default_value_t = 10)]
+        count: u32,
+
+        /// The account number of the derivation path.
+        #[arg(long, value_name = "NUM", default_value_t = 0)]
+        account: u32,
+
+        /// The first address index to list.
+        #[arg(long, value_name = "NUM", default_value_t = 0)]
+        start: u32,
+    },
+}
+
+/// Generates a new mnemonic and stores it in the keystore.
+fn add_key(
+    name: &str,
+    password: &str,
+    account_prefix: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let keystore = Keystore::open();
+    if keystore.contains(name)? {
+        return Err(format!("key '{}' already exists", name).into());
+    }
+    let mnemonic = Mnemonic::random(OsRng, Default::default());
+    let secret = KeySecret::Mnemonic {
+        phrase: mnemonic.phrase().to_string(),
+        password: password.to_string(),
+    };
+    let passphrase = read_new_passphrase()?;
+    let key = StoredKey::new(name, &secret, account_prefix, &passphrase)?;
+    keystore.add(&key)?;
+    Ok(serde_json::json!({
+        "name": key.name,
+        "address": key.address,
+        "pub_key": key.pub_key,
+        "mnemonic": mnemonic.phrase(),
+    }))
+}
+
+/// Imports an existing secret into the keystore.
+fn import_key(
+    name: &str,
+    mnemonic: Option<&str>,
+    private_key: Option<&str>,
+    password: &str,
+    account_prefix: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let keystore = Keystore::open();
+    if keystore.contains(name)? {
+        return Err(format!("key '{}' already exists", name).into());
+    }
+    let secret = match (mnemonic, private_key) {
+        (_, Some(private_key)) => KeySecret::PrivateKey {
+            hex: private_key.to_string(),
+        },
+        (Some(mnemonic), None) => KeySecret::Mnemonic {
+            phrase: mnemonic.to_string(),
+            password: password.to_string(),
+        },
+        (None, None) => KeySecret::Mnemonic {
+            phrase: read_secret("Enter mnemonic: ")?,
+            password: password.to_string(),
+        },
+    };
+    let passphrase = read_new_passphrase()?;
+    let key = StoredKey::new(name, &secret, account_prefix, &passphrase)?;
+    keystore.add(&key)?;
+    Ok(serde_json::json!({
+        "name": key.name,
+        "address": key.address,
+        "pub_key": key.pub_key,
+    }))
+}
+
+/// Lists all keys without unlocking them.
+fn list_keys() -> Result<Value, Box<dyn std::error::Error>> {
+    let keys = Keystore::open()
+        .list()?
+        .into_iter()
+        .map(|key| {
+            serde_json::json!({
+                "name": key.name,
+                "address": key.address,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(serde_json::json!(keys))
+}
+
+/// Shows public information about the key.
+fn show_key(name: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let key = Keystore::open().get(name)?;
+    Ok(serde_json::json!({
+        "name": key.name,
+        "address": key.address,
+        "pub_key": key.pub_key,
+    }))
+}
+
+/// Deletes the key after confirmation.
+fn delete_key(name: &str, yes: bool) -> Result<Value, Box<dyn std::error::Error>> {
+    let keystore = Keystore::open();
+    let key = keystore.get(name)?;
+    if !yes
+        && !confirm(&format!(
+            "Delete key '{}' ({})? It cannot be recovered without its mnemonic.",
+            key.name, key.address
+        ))?
+    {
+        return Err("aborted".into());
+    }
+    keystore.delete(name)?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("Key {} deleted successfully", name)
+    }))
+}
+
+/// Unlocks the key and returns its secret.
+fn export_key(name: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let keystore = Keystore::open();
+    let key = keystore.get(name)?;
+    let value = match keystore.unlock(name)? {
+        KeySecret::Mnemonic { phrase, .. } => serde_json::json!({
+            "name": key.name,
+            "address": key.address,
+            "mnemonic": phrase,
+        }),
+        KeySecret::PrivateKey { hex } => serde_json::json!({
+            "name": key.name,
+            "address": key.address,
+            "private_key": hex,
+        }),
+    };
+    Ok(value)
+}
+
+/// Derives addresses of the mnemonic at consecutive address indices.
+fn derive_keys(
+    name: Option<&str>,
+    mnemonic: Option<&str>,
+    password: &str,
+    account_prefix: &str,
+    count: u32,
+    account: u32,
+    start: u32,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let secret = match (name, mnemonic) {
+        (Some(name), _) => Keystore::open().unlock(name)?,
+        (None, Some(mnemonic)) => KeySecret::Mnemonic {
+            phrase: mnemonic.to_string(),
+            password: password.to_string(),
+        },
+        (None, None) => KeySecret::Mnemonic {
+            phrase: read_secret("Enter mnemonic: ")?,
+            password: password.to_string(),
+        },
+    };
+    if let KeySecret::PrivateKey { .. } = secret {
+        return Err("addresses can only be derived from a mnemonic".into());
+    }
+    let end = start
+        .checked_add(count)
+        .ok_or("address index is out of range")?;
+    let keys = (start..end)
+        .map(|index| {
+            let path = hd_path(account, index);
+            let address = secret
+                .signing_key_at(&path)?
+                .public_key()
+                .account_id(account_prefix)
+                .map_err(|err| err.to_string())?;
+            Ok(serde_json::json!({
+                "index": index,
+                "hd_path": path,
+                "address": address.to_string(),
+            }))
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    Ok(serde_json::json!(keys))
+}
@@ -14,19 +14,19 @@ use std::os::unix::process::ExitStatusExt;
 use std::path::{self, Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tempdir::TempDir;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
-use crate::{print_object, read_file, OutputFormat};
+use crate::{print_object, read_file, OutputArgs};
 
 type Error = Box<dyn std::error::Error>;
 type Result<T> = std::result::Result<T, Error>;
 
-/// Local VM run arguments.
-#[derive(Clone, Debug, clap::Parser)]
-pub struct RunArgs {
+/// VM arguments.
+#[derive(Clone, Debug, clap::Args)]
+pub struct VmArgs {
     /// Path to QEMU executable.
     ///
     /// If not specified, it will be auto-detected.
@@ -36,6 +36,17 @@ pub struct RunArgs {
     /// Additional QEMU arguments.
     #[arg(long = "qemu-arg", value_name = "ARGS", allow_hyphen_values = true)]
     qemu_args: Vec<String>,
+}
+
+/// Local VM run arguments.
+#[derive(Clone, Debug, clap::Parser)]
+pub struct RunArgs {
+    #[command(flatten)]
+    vm_args: VmArgs,
+
+    /// PCI device path to GPU device.
+    #[arg(short = 'g', long)]
+    gpu: Vec<String>,
 
     /// Task file. Only task specification is taken into account.
     ///
@@ -55,10 +66,6 @@ pub struct RunArgs {
     #[arg(short = 'm', long)]
     mem: Option<u32>,
 
-    /// PCI device path to GPU device.
-    #[arg(short = 'g', long)]
-    gpu: Vec<String>,
-
     /// Environment variables to set for main program. Appends to provided in task file.
     ///
     /// Example: --env KEY=VALUE
@@ -241,32 +248,112 @@ const DEFAULT_QEMU: &str = "qemu-system-x86_64";
 const GEVULOT_RT_CONFIG_TAG: &str = "gevulot-rt-config";
 const GEVULOT_OUTPUT_TAG: &str = "gevulot-output";
 const GEVULOT_INPUT_TAG: &str = "gevulot-input";
-const GEVULOT_INPUT_MOUNTPOINT: &str = "/mnt/gevulot/input/";
-const GEVULOT_OUTPUT_MOUNTPOINT: &str = "/mnt/gevulot/output/";
+pub(crate) const GEVULOT_INPUT_MOUNTPOINT: &str = "/mnt/gevulot/input/";
+pub(crate) const GEVULOT_OUTPUT_MOUNTPOINT: &str = "/mnt/gevulot/output/";
 
 const DEBUG_EXIT: DebugExit = DebugExit::default_x86();
 
+/// Returns the path of the file under the VM mountpoint relative to it.
+///
+/// Only paths with plain components are accepted, so files of the task
+/// are never read or written outside of the host directory mounted there.
+pub(crate) fn mount_relative_path(path: &str, mountpoint: &str) -> Result<PathBuf> {
+    let relative = path
+        .strip_prefix(mountpoint)
+        .ok_or_else(|| format!("path '{}' must be under {}", path, mountpoint))?;
+    if !is_plain_relative(Path::new(relative)) {
+        return Err(format!("path '{}' must be a file path under {}", path, mountpoint).into());
+    }
+    Ok(PathBuf::from(relative))
+}
+
+/// Returns `true` if the path is non-empty and consists of normal components only.
+pub(crate) fn is_plain_relative(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, path::Component::Normal(_)))
+}
+
 impl RunArgs {
     /// Run local-run subcommand.
-    pub async fn run(&self, format: OutputFormat) -> Result<()> {
+    pub async fn run(&self, output: &OutputArgs) -> Result<()> {
         let value = run(self).await?;
-        print_object(format, &value)
+        print_object(output, &value)
     }
 }
 
 async fn run(run_args: &RunArgs) -> anyhow::Result<Value> {
     let into_anyhow = |err: Error| -> anyhow::Error { anyhow::anyhow!(err.to_string()) };
 
-    let qemu_path = resolve_qemu(run_args.qemu_path.as_ref())
-        .map_err(into_anyhow)
-        .context("failed to find QEMU executable")?;
-    debug!("resolved QEMU: {}", qemu_path.display());
-
-    let mut task_spec = get_task_spec(run_args)
+    let task_spec = get_task_spec(run_args)
         .await
         .map_err(into_anyhow)
         .context("failed to compile task specification")?;
 
+    let execution = execute(
+        &run_args.vm_args,
+        &run_args.gpu,
+        task_spec,
+        &run_args.output_dir,
+        run_args.stdout,
+        run_args.stderr,
+    )
+    .await?;
+    if let Some(err) = execution.error {
+        return Err(anyhow::anyhow!(err)).context("VM execution failed");
+    }
+
+    Ok(serde_json::json!({
+        "message": "main program exited successfully",
+        "execution_time": execution.execution_time.as_secs(),
+        "output_contexts": execution.output_contexts,
+        "stdout": execution.stdout,
+        "stderr": execution.stderr,
+    }))
+}
+
+/// Result of the task executed in VM.
+#[derive(Debug)]
+pub struct Execution {
+    /// Error of the main program or VM. `None` if the main program exited successfully.
+    pub error: Option<String>,
+
+    pub execution_time: Duration,
+
+    /// Exit code of the VM, `0` if the main program exited successfully.
+    /// `None` if the VM was terminated by a signal.
+    pub exit_code: Option<i32>,
+
+    /// Stored output contexts. Empty if the execution failed.
+    pub output_contexts: Vec<PathBuf>,
+
+    /// File with stored stdout.
+    pub stdout: Option<PathBuf>,
+
+    /// File with stored stderr.
+    pub stderr: Option<PathBuf>,
+}
+
+/// Runs the task in VM and stores its outputs into `output_dir`.
+///
+/// Failure of the main program or VM is reported in the execution result,
+/// errors of preparing the VM are returned.
+pub async fn execute(
+    vm_args: &VmArgs,
+    gpu: &[String],
+    mut task_spec: TaskSpec,
+    output_dir: &Path,
+    print_stdout: bool,
+    print_stderr: bool,
+) -> anyhow::Result<Execution> {
+    let into_anyhow = |err: Error| -> anyhow::Error { anyhow::anyhow!(err.to_string()) };
+
+    let qemu_path = resolve_qemu(vm_args.qemu_path.as_ref())
+        .map_err(into_anyhow)
+        .context("failed to find QEMU executable")?;
+    debug!("resolved QEMU: {}", qemu_path.display());
+
     let runtime_cfg = generate_runtime_config(&task_spec).await;
     debug!("runtime config: {:#?}", &runtime_cfg);
 
@@ -297,7 +384,7 @@ async fn run(run_args: &RunArgs) -> anyhow::Result<Value> {
         .map_err(into_anyhow)
         .context("failed to prepare VM image")?;
 
-    let qemu_args = run_args
+    let qemu_args = vm_args
         .qemu_args
         .iter()
         .flat_map(|arg| arg.split(' '))
@@ -305,54 +392,53 @@ async fn run(run_args: &RunArgs) -> anyhow::Result<Value> {
 
     debug!("task specification: {:#?}", &task_spec);
 
-    let cmd = build_cmd(
-        &qemu_path,
-        &task_spec,
-        &runtime_dirs,
-        &run_args.gpu,
-        &qemu_args,
-    )
-    .map_err(into_anyhow)
-    .context("failed to generate QEMU arguments")?;
+    let cmd = build_cmd(&qemu_path, &task_spec, &runtime_dirs, gpu, &qemu_args)
+        .map_err(into_anyhow)
+        .context("failed to generate QEMU arguments")?;
     debug!("QEMU cmd: {:#?}", &cmd);
 
     if !task_spec.output_contexts.is_empty() || task_spec.store_stdout || task_spec.store_stderr {
-        fs::create_dir_all(&run_args.output_dir)
+        fs::create_dir_all(output_dir)
             .await
             .context("failed to create output directory")?;
     }
 
-    let stdout_file = task_spec
-        .store_stdout
-        .then(|| run_args.output_dir.join("stdout"));
-    let stderr_file = task_spec
-        .store_stderr
-        .then(|| run_args.output_dir.join("stderr"));
+    let stdout_file = task_spec.store_stdout.then(|| output_dir.join("stdout"));
+    let stderr_file = task_spec.store_stderr.then(|| output_dir.join("stderr"));
 
     let timestamp = Instant::now();
-    run_cmd(
+    let result = run_cmd(
         cmd,
         stdout_file.clone(),
         stderr_file.clone(),
-        run_args.stdout,
-        run_args.stderr,
-    )
-    .map_err(into_anyhow)
-    .context("VM execution failed")?;
+        print_stdout,
+        print_stderr,
+    );
     let execution_time = timestamp.elapsed();
+    let exit_code = result
+        .as_ref()
+        .ok()
+        .and_then(|status| vm_exit_code(*status));
+
+    let (error, output_contexts) = match result.and_then(process_exit_status) {
+        Ok(()) => {
+            let output_paths = store_outputs(output_dir, &task_spec, &runtime_dirs.output)
+                .await
+                .map_err(into_anyhow)
+                .context("failed to store output context")?;
+            (None, output_paths)
+        }
+        Err(err) => (Some(err.to_string()), vec![]),
+    };
 
-    let output_paths = store_outputs(run_args, &task_spec, &runtime_dirs.output)
-        .await
-        .map_err(into_anyhow)
-        .context("failed to store output context")?;
-
-    Ok(serde_json::json!({
-        "message": "main program exited successfully",
-        "execution_time": execution_time.as_secs(),
-        "output_contexts": output_paths,
-        "stdout": stdout_file,
-        "stderr": stderr_file,
-    }))
+    Ok(Execution {
+        error,
+        execution_time,
+        exit_code,
+        output_contexts,
+        stdout: stdout_file,
+        stderr: stderr_file,
+    })
 }
 
 fn create_input_context(run_input: &RunInput) -> Result<InputContext> {
@@ -581,19 +667,18 @@ async fn prepare_inputs(task_spec: &mut TaskSpec, runtime_input: &Path) -> Resul
             tokio::fs::metadata(path)
                 .await
                 .map_err::<Error, _>(|_| format!("input file not found: {}", path).into())?;
-            let relative = PathBuf::from(
-                input
-                    .target
-                    .strip_prefix(GEVULOT_INPUT_MOUNTPOINT)
-                    .expect("input target must be valid"),
-            );
+            let relative = mount_relative_path(&input.target, GEVULOT_INPUT_MOUNTPOINT)?;
             if let Some(parent) = relative.parent() {
                 fs::create_dir_all(runtime_input.join(parent)).await?;
             }
             fs::copy(path, runtime_input.join(relative)).await?;
         } else {
-            // schedule remote source for downloading
-            inputs_to_download.push(Download::new(&input.source));
+            // schedule remote source for downloading to its target
+            let relative = mount_relative_path(&input.target, GEVULOT_INPUT_MOUNTPOINT)?;
+            if let Some(parent) = relative.parent() {
+                fs::create_dir_all(runtime_input.join(parent)).await?;
+            }
+            inputs_to_download.push(Download::new(&input.source).file_name(&relative));
             inputs_sources.push(&mut input.source);
         }
     }
@@ -633,23 +718,18 @@ async fn prepare_inputs(task_spec: &mut TaskSpec, runtime_input: &Path) -> Resul
 }
 
 async fn store_outputs(
-    run_args: &RunArgs,
+    output_dir: &Path,
     task_spec: &TaskSpec,
     runtime_output: &Path,
 ) -> Result<Vec<PathBuf>> {
     let mut output_paths = vec![];
     for output in &task_spec.output_contexts {
-        let relative = PathBuf::from(
-            output
-                .source
-                .strip_prefix(GEVULOT_OUTPUT_MOUNTPOINT)
-                .expect("output path must be valid"),
-        );
+        let relative = mount_relative_path(&output.source, GEVULOT_OUTPUT_MOUNTPOINT)?;
         let local = runtime_output.join(&relative);
         if let Some(parent) = local.parent() {
-            fs::create_dir_all(run_args.output_dir.join(parent)).await?;
+            fs::create_dir_all(output_dir.join(parent)).await?;
         }
-        let output_path = run_args.output_dir.join(&relative);
+        let output_path = output_dir.join(&relative);
         fs::copy(local, &output_path).await?;
         output_paths.push(output_path);
     }
@@ -764,7 +844,7 @@ fn run_cmd(
     stderr_file: Option<PathBuf>,
     print_stdout: bool,
     print_stderr: bool,
-) -> Result<()> {
+) -> Result<ExitStatus> {
     use std::fs;
     use std::io::{BufRead, BufReader, Write};
 
@@ -826,7 +906,15 @@ fn run_cmd(
     stdout_thread.join().expect("failed to join thread");
     stderr_thread.join().expect("failed to join thread");
 
-    process_exit_status(exit_status)
+    Ok(exit_status)
+}
+
+/// Returns exit code of the VM with the success code of the debug exit device mapped to `0`.
+fn vm_exit_code(exit_status: ExitStatus) -> Option<i32> {
+    let DebugExit::X86 { success_code, .. } = DEBUG_EXIT;
+    exit_status
+        .code()
+        .map(|code| if code == success_code as i32 { 0 } else { code })
 }
 
 /// Convert `ExitStatus` into meaningful error message if needed.
@@ -896,3 +984,22 @@ where
         Ok(which::which(DEFAULT_QEMU)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mount_relative_path() {
+        let relative = |path| mount_relative_path(path, GEVULOT_OUTPUT_MOUNTPOINT);
+        assert_eq!(
+            relative("/mnt/gevulot/output/dir/file.txt").unwrap(),
+            PathBuf::from("dir/file.txt")
+        );
+        assert!(relative("/mnt/gevulot/output/").is_err());
+        assert!(relative("/mnt/gevulot/output/../../../.bashrc").is_err());
+        assert!(relative("/mnt/gevulot/output//etc/passwd").is_err());
+        assert!(relative("/mnt/gevulot/input/file.txt").is_err());
+        assert!(relative("/etc/passwd").is_err());
+    }
+}
@@ -1,5 +1,15 @@
 //! Gevulot Control commands definition.
 
+use cosmrs::crypto::secp256k1::SigningKey;
+use cosmrs::proto::cosmos::base::query::v1beta1::PageRequest;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::keystore::{hd_path, KeySecret, Keystore};
+use crate::utils::get_field;
+
 /// Arguments for chain-related commands.
 #[derive(Clone, Debug, clap::Args)]
 pub struct ChainArgs {
@@ -83,12 +93,587 @@ pub struct ChainArgs {
         hide_env_values = true
     )]
     pub password: Option<String>,
+
+    /// Derivation path options for the mnemonic.
+    #[command(flatten)]
+    pub hd_path: HdPathArgs,
+
+    /// Sets the configuration profile to take missing settings from.
+    ///
+    /// Settings passed through CLI or environment variables take precedence over the profile.
+    /// If not specified, the current profile from configuration file is used.
+    #[arg(global = true, long, env = "GEVULOT_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Sets the name of the key from the local keystore to sign transactions with.
+    ///
+    /// The key is unlocked with the passphrase from GEVULOT_KEYSTORE_PASSPHRASE
+    /// or prompted interactively. Overrides mnemonic and private key.
+    ///
+    /// With --generate-only or --dry-run it can also be an address of the signer.
+    #[arg(global = true, long, env = "GEVULOT_KEY", value_name = "NAME")]
+    pub from: Option<String>,
+
+    /// Prints unsigned transaction instead of signing and broadcasting it.
+    ///
+    /// The transaction can be signed with `tx sign` and submitted with `tx broadcast`.
+    #[arg(global = true, long)]
+    pub generate_only: bool,
+
+    /// Simulates the transaction and prints estimated gas and fee instead of broadcasting it.
+    #[arg(global = true, long, conflicts_with = "generate_only")]
+    pub dry_run: bool,
+
+    /// Sets the account number of the signer instead of querying it from the chain.
+    #[arg(global = true, long, value_name = "NUM")]
+    pub account_number: Option<u64>,
+
+    /// Sets the sequence of the signer instead of querying it from the chain.
+    #[arg(global = true, long, value_name = "NUM")]
+    pub sequence: Option<u64>,
+
+    /// Set when settings are already taken from the profile.
+    #[arg(skip)]
+    resolved: bool,
+
+    /// Set when the keystore key is already unlocked.
+    #[arg(skip)]
+    unlocked: bool,
+}
+
+impl ChainArgs {
+    /// Fills missing settings from the configuration profile without unlocking keys.
+    ///
+    /// If an HD path is selected, the key is derived from the mnemonic.
+    pub fn resolve(&self) -> Result<ChainArgs, Box<dyn std::error::Error>> {
+        if self.resolved {
+            return Ok(self.clone());
+        }
+        let mut resolved = self.with_profile()?;
+        // Keystore key replaces mnemonic when it is unlocked, so it is derived then
+        if resolved.from.is_none() {
+            resolved = resolved.derive_key()?;
+        }
+        resolved.resolved = true;
+        Ok(resolved)
+    }
+
+    /// Resolves settings and unlocks the keystore key to sign transactions with.
+    ///
+    /// If a keystore key is selected, it is unlocked and used instead of mnemonic or private key.
+    /// Keys are not unlocked when transactions are not going to be signed.
+    pub fn unlock(&self) -> Result<ChainArgs, Box<dyn std::error::Error>> {
+        let mut resolved = self.resolve()?;
+        if resolved.unlocked || !resolved.signs_transactions() || resolved.from.is_none() {
+            return Ok(resolved);
+        }
+        resolved = resolved.unlock_key()?.derive_key()?;
+        resolved.unlocked = true;
+        Ok(resolved)
+    }
+
+    /// Resolves settings once for the whole command.
+    ///
+    /// The key is only unlocked if the command signs transactions,
+    /// so read-only commands never prompt for the passphrase.
+    pub fn resolve_for(&self, signs: bool) -> Result<ChainArgs, Box<dyn std::error::Error>> {
+        if signs {
+            self.unlock()
+        } else {
+            self.resolve()
+        }
+    }
+
+    /// Returns `true` if the selected keystore key is not unlocked,
+    /// so its address must be taken from the keystore.
+    pub fn is_locked(&self) -> bool {
+        self.from.is_some() && !self.unlocked
+    }
+
+    /// Returns `false` if transactions are only generated or simulated.
+    pub fn signs_transactions(&self) -> bool {
+        !self.generate_only && !self.dry_run
+    }
+
+    /// Fills missing settings from the configuration profile without unlocking keys.
+    pub fn with_profile(&self) -> Result<ChainArgs, Box<dyn std::error::Error>> {
+        if self.resolved {
+            return Ok(self.clone());
+        }
+        let config = Config::load()?;
+        let Some(profile) = config.profile(self.profile.as_deref())? else {
+            return Ok(self.clone());
+        };
+        let mut resolved = ChainArgs {
+            endpoint: self.endpoint.clone().or(profile.endpoint.clone()),
+            chain_id: self.chain_id.clone().or(profile.chain_id.clone()),
+            gas_price: self.gas_price.or(profile.gas_price),
+            gas_multiplier: self.gas_multiplier.or(profile.gas_multiplier),
+            gas_limit: self.gas_limit.or(profile.gas_limit),
+            ..self.clone()
+        };
+        // Credentials are taken as a whole, so that the profile never mixes its key
+        // with the one passed through CLI or environment variables.
+        let has_credentials = self.mnemonic.is_some()
+            || self.private_key.is_some()
+            || self.password.is_some()
+            || self.from.is_some();
+        if !has_credentials {
+            resolved.mnemonic = profile.mnemonic.clone();
+            resolved.private_key = profile.private_key.clone();
+            resolved.password = profile.password.clone();
+            resolved.from = profile.from.clone();
+        }
+        Ok(resolved)
+    }
+
+    /// Returns the key to sign transactions with.
+    pub fn signing_key(&self) -> Result<SigningKey, Box<dyn std::error::Error>> {
+        let resolved = self.unlock()?;
+        let secret = if let Some(hex) = resolved.private_key {
+            KeySecret::PrivateKey { hex }
+        } else if let Some(phrase) = resolved.mnemonic {
+            KeySecret::Mnemonic {
+                phrase,
+                password: resolved.password.unwrap_or_default(),
+            }
+        } else {
+            return Err("Either mnemonic, private key or key name must be provided".into());
+        };
+        secret.signing_key()
+    }
+
+    /// Replaces mnemonic and private key with the secret of the keystore key.
+    fn unlock_key(mut self) -> Result<ChainArgs, Box<dyn std::error::Error>> {
+        let Some(name) = &self.from else {
+            return Ok(self);
+        };
+        match Keystore::open().unlock(name)? {
+            KeySecret::Mnemonic { phrase, password } => {
+                self.mnemonic = Some(phrase);
+                self.password = Some(password);
+                self.private_key = None;
+            }
+            KeySecret::PrivateKey { hex } => {
+                self.private_key = Some(hex);
+                self.mnemonic = None;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Replaces mnemonic with the private key derived at the selected path.
+    ///
+    /// Does nothing if the default path is used, so the mnemonic is passed to the client as is.
+    fn derive_key(mut self) -> Result<ChainArgs, Box<dyn std::error::Error>> {
+        let Some(path) = self.hd_path.path() else {
+            return Ok(self);
+        };
+        if self.private_key.is_some() {
+            return Err("--account, --index and --hd-path can only be used with a mnemonic".into());
+        }
+        let Some(phrase) = self.mnemonic.take() else {
+            return Ok(self);
+        };
+        let secret = KeySecret::Mnemonic {
+            phrase,
+            password: self.password.take().unwrap_or_default(),
+        };
+        self.private_key = Some(hex::encode(secret.private_key_at(&path)?));
+        self.hd_path = HdPathArgs::default();
+        Ok(self)
+    }
+}
+
+/// Arguments selecting the BIP-44 derivation path of the key derived from a mnemonic.
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct HdPathArgs {
+    /// Sets the account number of the derivation path m/44'/118'/ACCOUNT'/0/INDEX.
+    #[arg(global = true, long, env = "GEVULOT_HD_ACCOUNT", value_name = "NUM")]
+    pub account: Option<u32>,
+
+    /// Sets the address index of the derivation path m/44'/118'/ACCOUNT'/0/INDEX.
+    #[arg(global = true, long, env = "GEVULOT_HD_INDEX", value_name = "NUM")]
+    pub index: Option<u32>,
+
+    /// Sets the full derivation path, e.g. m/44'/118'/0'/0/3.
+    #[arg(
+        global = true,
+        long,
+        env = "GEVULOT_HD_PATH",
+        value_name = "PATH",
+        conflicts_with_all = ["account", "index"]
+    )]
+    pub hd_path: Option<String>,
+}
+
+impl HdPathArgs {
+    /// Returns the derivation path if any of the options is set.
+    pub fn path(&self) -> Option<String> {
+        match (&self.hd_path, self.account, self.index) {
+            (Some(path), _, _) => Some(path.clone()),
+            (None, None, None) => None,
+            (None, account, index) => Some(hd_path(account.unwrap_or(0), index.unwrap_or(0))),
+        }
+    }
 }
 
+/// Arguments for filtering, sorting and paginating listed objects.
+///
+/// Gevulot queries support pagination but no filters, so filters and sorting are applied
+/// on the client side. Only a page of objects which are neither filtered nor sorted
+/// is queried from the chain.
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct ListArgs {
+    /// Only list objects created by this address.
+    #[arg(long, value_name = "ADDRESS")]
+    pub creator: Option<String>,
+
+    /// Only list objects created by the address of the configured key.
+    #[arg(long, conflicts_with = "creator")]
+    pub mine: bool,
+
+    /// Only list objects with this label. Can be passed multiple times.
+    ///
+    /// Example: --label env=staging
+    #[arg(long = "label", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub labels: Vec<(String, String)>,
+
+    /// Only list objects with this tag. Can be passed multiple times.
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Only list objects in this state (e.g. pending, running, done, failed).
+    #[arg(long)]
+    pub state: Option<String>,
+
+    /// Only list objects created after this point in time.
+    ///
+    /// Accepts either RFC 3339 timestamp (2024-01-01T00:00:00Z)
+    /// or a duration relative to now (e.g. 30m, 2h, 7days).
+    #[arg(long, value_name = "TIME", value_parser = parse_since)]
+    pub since: Option<u64>,
+
+    /// Sort objects by the field (e.g. metadata.name or status.createdAt).
+    #[arg(long, value_name = "FIELD")]
+    pub sort_by: Option<String>,
+
+    /// Reverse the sorting order.
+    #[arg(long, requires = "sort_by")]
+    pub reverse: bool,
+
+    /// Maximum number of objects to list.
+    #[arg(long, value_name = "NUM")]
+    pub limit: Option<usize>,
+
+    /// Page number to list, starting from 1. Page size is set by --limit.
+    #[arg(long, value_name = "NUM", default_value_t = 1, requires = "limit")]
+    pub page: usize,
+}
+
+impl ListArgs {
+    /// Returns the page to query from the chain, if objects are paginated but not filtered or sorted.
+    pub fn page_request(&self) -> Option<PageRequest> {
+        let limit = self.limit?;
+        let filtered = self.creator.is_some()
+            || self.mine
+            || !self.labels.is_empty()
+            || !self.tags.is_empty()
+            || self.state.is_some()
+            || self.since.is_some()
+            || self.sort_by.is_some();
+        if filtered {
+            return None;
+        }
+        Some(PageRequest {
+            offset: self.page.saturating_sub(1).saturating_mul(limit) as u64,
+            limit: limit as u64,
+            ..Default::default()
+        })
+    }
+
+    /// Returns the arguments without pagination, for objects already paginated by the chain.
+    pub fn without_pagination(&self) -> ListArgs {
+        ListArgs {
+            limit: None,
+            page: 1,
+            ..self.clone()
+        }
+    }
+
+    /// Filters, sorts and paginates objects.
+    ///
+    /// `me` is the address used for `--mine` filter.
+    pub fn apply(&self, items: Vec<Value>, me: Option<&str>) -> Vec<Value> {
+        let creator = if self.mine {
+            me
+        } else {
+            self.creator.as_deref()
+        };
+
+        let mut items = items
+            .into_iter()
+            .filter(|item| {
+                creator.is_none_or(|creator| {
+                    get_field(item, "metadata.creator").and_then(Value::as_str) == Some(creator)
+                })
+            })
+            .filter(|item| {
+                self.labels.iter().all(|(key, value)| {
+                    get_field(item, "metadata.labels")
+                        .and_then(Value::as_array)
+                        .is_some_and(|labels| {
+                            labels.iter().any(|label| {
+                                label["key"].as_str() == Some(key)
+                                    && label["value"].as_str() == Some(value)
+                            })
+                        })
+                })
+            })
+            .filter(|item| {
+                self.tags.iter().all(|tag| {
+                    get_field(item, "metadata.tags")
+                        .and_then(Value::as_array)
+                        .is_some_and(|tags| tags.iter().any(|t| t.as_str() == Some(tag)))
+                })
+            })
+            .filter(|item| {
+                self.state.as_ref().is_none_or(|state| {
+                    get_field(item, "status.state")
+                        .and_then(Value::as_str)
+                        .is_some_and(|s| s.eq_ignore_ascii_case(state))
+                })
+            })
+            .filter(|item| {
+                self.since.is_none_or(|since| {
+                    created_at(item).is_some_and(|created_at| created_at >= since)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(field) = &self.sort_by {
+            items.sort_by(|a, b| compare_values(get_field(a, field), get_field(b, field)));
+            if self.reverse {
+                items.reverse();
+            }
+        }
+
+        if let Some(limit) = self.limit {
+            let skip = self.page.saturating_sub(1).saturating_mul(limit);
+            items = items.into_iter().skip(skip).take(limit).collect();
+        }
+
+        items
+    }
+}
+
+/// Arguments controlling how to wait for an object to reach the expected state.
+#[derive(Clone, Debug, clap::Args)]
+pub struct WaitArgs {
+    /// Maximum time to wait in seconds. Waits forever if not specified.
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Interval between status queries in seconds.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub interval: u64,
+}
+
+impl WaitArgs {
+    /// Returns an error if the timeout has elapsed since `started`.
+    pub fn check_timeout(
+        &self,
+        started: Instant,
+        what: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.timeout {
+            Some(timeout) if started.elapsed() >= Duration::from_secs(timeout) => {
+                Err(format!("timed out after {}s waiting for {}", timeout, what).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Returns creation time of the object as UNIX timestamp in seconds.
+fn created_at(item: &Value) -> Option<u64> {
+    ["status.createdAt", "metadata.createdAt"]
+        .iter()
+        .filter_map(|path| get_field(item, path))
+        .find_map(|value| {
+            value
+                .as_u64()
+                .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+        })
+}
+
+/// Compares optional JSON values. Missing values are ordered last.
+fn compare_values(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a
+            .as_f64()
+            .partial_cmp(&b.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Some(Value::String(a)), Some(Value::String(b))) => a.cmp(b),
+        (Some(Value::Bool(a)), Some(Value::Bool(b))) => a.cmp(b),
+        (Some(a), Some(b)) => a.to_string().cmp(&b.to_string()),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Parses `KEY=VALUE` argument.
+pub fn parse_key_value(arg: &str) -> Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", arg))
+}
+
+/// Parses `--since` argument into UNIX timestamp in seconds.
+fn parse_since(arg: &str) -> Result<u64, String> {
+    let time = match humantime::parse_rfc3339_weak(arg) {
+        Ok(time) => time,
+        Err(_) => {
+            let duration: Duration = humantime::parse_duration(arg)
+                .map_err(|_| format!("expected RFC 3339 timestamp or duration, got '{}'", arg))?;
+            SystemTime::now()
+                .checked_sub(duration)
+                .ok_or_else(|| format!("duration is too large: {}", arg))?
+        }
+    };
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .map_err(|err| err.to_string())
+}
+
+pub mod account;
+pub mod apply;
+pub mod batch;
 pub mod build;
+pub mod config;
+pub mod keys;
 pub mod local_run;
 pub mod pins;
+pub mod send;
 pub mod sudo;
 pub mod tasks;
+pub mod tx;
+pub mod validate;
 pub mod workers;
 pub mod workflow;
+
+#[cfg(test)]
+mod tests {
+    use super::ListArgs;
+    use serde_json::{json, Value};
+
+    fn objects() -> Vec<Value> {
+        vec![
+            json!({
+                "metadata": {
+                    "id": "1",
+                    "creator": "gvlt1alice",
+                    "tags": ["gpu"],
+                    "labels": [{ "key": "env", "value": "staging" }],
+                },
+                "status": { "state": "Done", "createdAt": 100 },
+            }),
+            json!({
+                "metadata": {
+                    "id": "2",
+                    "creator": "gvlt1bob",
+                    "tags": [],
+                    "labels": [{ "key": "env", "value": "prod" }],
+                },
+                "status": { "state": "Failed", "createdAt": 300 },
+            }),
+            json!({
+                "metadata": {
+                    "id": "3",
+                    "creator": "gvlt1alice",
+                    "tags": ["gpu", "cpu"],
+                    "labels": [],
+                },
+                "status": { "state": "Running", "createdAt": 200 },
+            }),
+        ]
+    }
+
+    fn ids(items: &[Value]) -> Vec<&str> {
+        items
+            .iter()
+            .map(|item| item["metadata"]["id"].as_str().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_filters() {
+        let args = ListArgs {
+            creator: Some("gvlt1alice".to_string()),
+            tags: vec!["gpu".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(ids(&args.apply(objects(), None)), ["1", "3"]);
+
+        let args = ListArgs {
+            labels: vec![("env".to_string(), "prod".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(ids(&args.apply(objects(), None)), ["2"]);
+
+        let args = ListArgs {
+            state: Some("running".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&args.apply(objects(), None)), ["3"]);
+
+        let args = ListArgs {
+            mine: true,
+            since: Some(150),
+            ..Default::default()
+        };
+        assert_eq!(ids(&args.apply(objects(), Some("gvlt1alice"))), ["3"]);
+    }
+
+    #[test]
+    fn test_sort_and_paginate() {
+        let args = ListArgs {
+            sort_by: Some("status.createdAt".to_string()),
+            reverse: true,
+            limit: Some(2),
+            page: 1,
+            ..Default::default()
+        };
+        assert_eq!(ids(&args.apply(objects(), None)), ["2", "3"]);
+
+        let args = ListArgs { page: 2, ..args };
+        assert_eq!(ids(&args.apply(objects(), None)), ["1"]);
+        // Sorted objects cannot be paginated by the chain
+        assert!(args.page_request().is_none());
+    }
+
+    #[test]
+    fn test_page_request() {
+        let args = ListArgs {
+            limit: Some(20),
+            page: 3,
+            ..Default::default()
+        };
+        let page = args.page_request().unwrap();
+        assert_eq!((page.offset, page.limit), (40, 20));
+        assert_eq!(args.without_pagination().limit, None);
+
+        assert!(ListArgs::default().page_request().is_none());
+        let args = ListArgs {
+            state: Some("done".to_string()),
+            ..args
+        };
+        assert!(args.page_request().is_none());
+    }
+}
@@ -1,11 +1,17 @@
 use gevulot_rs::builders::{
     ByteSize, ByteUnit, MsgAckPinBuilder, MsgCreatePinBuilder, MsgDeletePinBuilder,
 };
+use gevulot_rs::proto::gevulot::gevulot::{MsgCreatePin, MsgCreatePinResponse, QueryAllPinRequest};
 use patharg::InputArg;
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use crate::{connect_to_gevulot, print_object, read_file, ChainArgs, OutputFormat};
+use crate::commands::batch::{self, BatchArgs};
+use crate::commands::tx::{intercept, signer_address};
+use crate::template::TemplateArgs;
+use crate::unixfs;
+use crate::{connect_to_gevulot, print_object, ChainArgs, ListArgs, OutputArgs, WaitArgs};
 
 /// Pins command.
 #[derive(Clone, Debug, clap::Parser)]
@@ -19,22 +25,48 @@ pub struct Command {
 
 impl Command {
     /// Match pin subcommand and run it.
-    pub async fn run(&self, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let chain_args = &self.chain_args.resolve_for(self.subcommand.signs())?;
         let value = match &self.subcommand {
-            Subcommand::List => list_pins(&self.chain_args).await,
-            Subcommand::Get { cid } => get_pin(&self.chain_args, cid).await,
+            Subcommand::List { list_args } => list_pins(chain_args, list_args).await,
+            Subcommand::Get { cid } => get_pin(chain_args, cid).await,
+            Subcommand::Status { cid } => pin_status(chain_args, cid).await,
+            Subcommand::Wait { cid, wait_args } => wait_pin(chain_args, cid, wait_args).await,
             Subcommand::Ack {
                 cid,
                 id,
                 worker_id,
                 success,
-            } => ack_pin(&self.chain_args, id, cid, worker_id, *success).await,
-            Subcommand::Create { file } => {
-                create_pin(&self.chain_args, file.path_ref().map(|v| &**v)).await
+            } => ack_pin(chain_args, id, cid, worker_id, *success).await,
+            Subcommand::Create {
+                file,
+                batch_args,
+                template_args,
+            } => {
+                let path = file.path_ref().map(|v| &**v);
+                let mut documents = template_args.read_documents(path).await?;
+                if documents.is_empty() {
+                    return Err("no pin documents found".into());
+                }
+                if documents.len() > 1 || path.is_some_and(Path::is_dir) {
+                    batch::create_all(
+                        chain_args,
+                        &connect_to_gevulot(chain_args).await?,
+                        documents,
+                        batch_args,
+                        create_pin_msg,
+                        |resp: MsgCreatePinResponse| resp.id,
+                    )
+                    .await
+                } else {
+                    let pin = serde_yaml::from_str(&documents.remove(0).content)?;
+                    create_pin(chain_args, pin).await
+                }
             }
-            Subcommand::Delete { cid } => delete_pin(&self.chain_args, cid).await,
+            Subcommand::Add(add_args) => add_pin(chain_args, add_args).await,
+            Subcommand::Delete { cid } => delete_pin(chain_args, cid).await,
         }?;
-        print_object(format, &value)
+        print_object(output, &value)
     }
 }
 
@@ -42,7 +74,10 @@ impl Command {
 #[derive(Clone, Debug, clap::Subcommand)]
 enum Subcommand {
     /// List all pins.
-    List,
+    List {
+        #[command(flatten)]
+        list_args: ListArgs,
+    },
 
     /// Get a specific pin.
     Get {
@@ -50,6 +85,24 @@ enum Subcommand {
         cid: String,
     },
 
+    /// Show how many workers acknowledged the pin compared to its redundancy.
+    Status {
+        /// The CID of the pin.
+        cid: String,
+    },
+
+    /// Wait until the pin is acknowledged by as many workers as its redundancy.
+    ///
+    /// Progress is reported to stderr. When the redundancy is met,
+    /// the replication status is printed.
+    Wait {
+        /// The CID of the pin to wait for.
+        cid: String,
+
+        #[command(flatten)]
+        wait_args: WaitArgs,
+    },
+
     /// Ack a pin
     Ack {
         /// The ID of the pin to ack.
@@ -67,12 +120,27 @@ enum Subcommand {
     },
 
     /// Create a new pin.
+    ///
+    /// Multiple pins can be created at once from a multi-document YAML file
+    /// or from a directory of pin files.
     Create {
-        /// The file to read the pin data from or '-' to read from stdin.
+        /// The file or directory to read the pin data from or '-' to read from stdin.
         #[arg(short, long, default_value_t)]
         file: InputArg,
+
+        #[command(flatten)]
+        batch_args: BatchArgs,
+
+        #[command(flatten)]
+        template_args: TemplateArgs,
     },
 
+    /// Add a file or directory and create a pin for it.
+    ///
+    /// The content is chunked into a UnixFS DAG like `ipfs add --cid-version 1` does,
+    /// its CID and content size are computed locally and used in the created pin.
+    Add(AddArgs),
+
     /// Delete a pin.
     Delete {
         /// The CID of the pin to delete.
@@ -80,13 +148,100 @@ enum Subcommand {
     },
 }
 
+impl Subcommand {
+    /// Returns `true` if the subcommand signs transactions.
+    fn signs(&self) -> bool {
+        match self {
+            Subcommand::List { .. }
+            | Subcommand::Get { .. }
+            | Subcommand::Status { .. }
+            | Subcommand::Wait { .. } => false,
+            Subcommand::Add(add_args) => !add_args.only_hash,
+            _ => true,
+        }
+    }
+}
+
+/// Pin add arguments.
+#[derive(Clone, Debug, clap::Args)]
+struct AddArgs {
+    /// The file or directory to add.
+    path: PathBuf,
+
+    /// Name of the pin. Defaults to the file name.
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Description of the pin.
+    #[arg(long, default_value_t)]
+    description: String,
+
+    /// How long the content should be pinned in seconds.
+    #[arg(long, value_name = "SECONDS", default_value_t = 3600)]
+    time: u64,
+
+    /// Number of workers which should pin the content.
+    #[arg(long, default_value_t = 1)]
+    redundancy: u64,
+
+    /// URL the content can be downloaded from if it is not available in IPFS.
+    /// Can be repeated.
+    #[arg(long = "fallback-url", value_name = "URL")]
+    fallback_urls: Vec<String>,
+
+    /// Include hidden files of directories.
+    #[arg(long)]
+    hidden: bool,
+
+    /// Write the DAG to the CAR file.
+    #[arg(long, value_name = "FILE")]
+    car: Option<PathBuf>,
+
+    /// IPFS HTTP API to import the content to before creating the pin,
+    /// e.g. http://127.0.0.1:5001.
+    #[arg(long, value_name = "URL", env = "GEVULOT_IPFS_API")]
+    ipfs_api: Option<String>,
+
+    /// Only compute the CID, don't import the content or create the pin.
+    #[arg(long)]
+    only_hash: bool,
+}
+
 /// Lists all pins in the Gevulot network
-async fn list_pins(chain_args: &ChainArgs) -> Result<Value, Box<dyn std::error::Error>> {
+async fn list_pins(
+    chain_args: &ChainArgs,
+    list_args: &ListArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let pins = client.pins.list().await?;
+    let me = if list_args.mine {
+        Some(signer_address(chain_args, &client).await?)
+    } else {
+        None
+    };
+    let (pins, list_args) = match list_args.page_request() {
+        Some(pagination) => {
+            let request = QueryAllPinRequest {
+                pagination: Some(pagination),
+            };
+            let pins = client
+                .base_client
+                .write()
+                .await
+                .gevulot_client
+                .pin_all(request)
+                .await?
+                .into_inner()
+                .pin;
+            (pins, list_args.without_pagination())
+        }
+        None => (client.pins.list().await?, list_args.clone()),
+    };
     // Convert the pins to the gevulot_rs::models::Pin type
-    let pins: Vec<gevulot_rs::models::Pin> = pins.into_iter().map(Into::into).collect();
-    Ok(serde_json::json!(pins))
+    let pins = pins
+        .into_iter()
+        .map(|pin| serde_json::json!(gevulot_rs::models::Pin::from(pin)))
+        .collect();
+    Ok(serde_json::json!(list_args.apply(pins, me.as_deref())))
 }
 
 /// Retrieves a specific pin from the Gevulot network
@@ -101,6 +256,101 @@ async fn get_pin(
     Ok(serde_json::json!(pin))
 }
 
+/// Retrieves the pin and summarizes its replication
+async fn pin_status(
+    chain_args: &ChainArgs,
+    pin_cid: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let pin = get_pin(chain_args, pin_cid).await?;
+    Ok(replication_status(&pin))
+}
+
+/// Waits until the pin is acknowledged by enough workers
+async fn wait_pin(
+    chain_args: &ChainArgs,
+    pin_cid: &str,
+    wait_args: &WaitArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut client = connect_to_gevulot(chain_args).await?;
+    let started = Instant::now();
+    let mut last_progress = None;
+    loop {
+        let pin: gevulot_rs::models::Pin = client.pins.get(pin_cid).await?.into();
+        let status = replication_status(&serde_json::json!(pin));
+
+        let progress = (status["acked"].clone(), status["failed"].clone());
+        if last_progress.as_ref() != Some(&progress) {
+            eprintln!(
+                "Pin {}: {}/{} workers acknowledged, {} failed",
+                pin_cid, status["acked"], status["redundancy"], status["failed"]
+            );
+            last_progress = Some(progress);
+        }
+
+        if status["replicated"].as_bool() == Some(true) {
+            return Ok(status);
+        }
+
+        wait_args.check_timeout(started, &format!("pin {}", pin_cid))?;
+        tokio::time::sleep(Duration::from_secs(wait_args.interval)).await;
+    }
+}
+
+/// Summarizes acknowledgements of workers assigned to the pin.
+///
+/// Only the last acknowledgement of every worker is taken into account.
+fn replication_status(pin: &Value) -> Value {
+    let redundancy = pin["spec"]["redundancy"].as_u64().unwrap_or(1);
+    let mut workers: Vec<(String, Value)> = pin["status"]["assignedWorkers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|worker| {
+            (
+                worker.to_string(),
+                serde_json::json!({ "worker": worker, "status": "pending" }),
+            )
+        })
+        .collect();
+    for ack in pin["status"]["workerAcks"].as_array().into_iter().flatten() {
+        let Some(worker) = ack["worker"].as_str() else {
+            continue;
+        };
+        let mut record = serde_json::json!({ "worker": worker });
+        if ack["success"].as_bool() == Some(true) {
+            record["status"] = "acked".into();
+        } else {
+            record["status"] = "failed".into();
+            if let Some(error) = ack["error"].as_str().filter(|error| !error.is_empty()) {
+                record["error"] = error.into();
+            }
+        }
+        match workers.iter_mut().find(|(id, _)| id == worker) {
+            Some((_, existing)) => *existing = record,
+            None => workers.push((worker.to_string(), record)),
+        }
+    }
+
+    let count = |status: &str| {
+        workers
+            .iter()
+            .filter(|(_, record)| record["status"] == status)
+            .count()
+    };
+    let acked = count("acked");
+    serde_json::json!({
+        "cid": pin["spec"]["cid"],
+        "id": pin["metadata"]["id"],
+        "redundancy": redundancy,
+        "acked": acked,
+        "failed": count("failed"),
+        "pending": count("pending"),
+        "replicated": acked as u64 >= redundancy,
+        "workers": workers.into_iter().map(|(_, record)| record).collect::<Vec<_>>(),
+    })
+}
+
 /// Ack a specific pin
 async fn ack_pin(
     chain_args: &ChainArgs,
@@ -110,67 +360,36 @@ async fn ack_pin(
     success: bool,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
-    client
-        .pins
-        .ack(
-            MsgAckPinBuilder::default()
-                .id(pin_id.to_string())
-                .creator(me.clone())
-                .cid(pin_cid.to_string())
-                .worker_id(worker_id.to_string())
-                .success(success)
-                .into_message()?,
-        )
-        .await?;
+    let me = signer_address(chain_args, &client).await?;
+    let msg = MsgAckPinBuilder::default()
+        .id(pin_id.to_string())
+        .creator(me.clone())
+        .cid(pin_cid.to_string())
+        .worker_id(worker_id.to_string())
+        .success(success)
+        .into_message()?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.pins.ack(msg).await?;
     Ok(serde_json::json!({}))
 }
 
 /// Creates a new pin in the Gevulot network
 async fn create_pin(
     chain_args: &ChainArgs,
-    file: Option<&Path>,
+    pin: gevulot_rs::models::Pin,
 ) -> Result<Value, Box<dyn std::error::Error>> {
-    // Read pin data from file
-    let pin: gevulot_rs::models::Pin = read_file(file).await?;
     let mut client = connect_to_gevulot(chain_args).await?;
 
     // Get the client's address
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
+    let me = signer_address(chain_args, &client).await?;
 
-    // Create the pin using the MsgCreatePinBuilder
-    let resp = client
-        .pins
-        .create(
-            MsgCreatePinBuilder::default()
-                .creator(me.clone())
-                .cid(pin.spec.cid.clone())
-                .fallback_urls(pin.spec.fallback_urls.unwrap_or_default())
-                .bytes(ByteSize::new(
-                    pin.spec.bytes.bytes()? as u64,
-                    ByteUnit::Byte,
-                ))
-                .time(pin.spec.time.seconds()? as u64)
-                .redundancy(pin.spec.redundancy as u64)
-                .name(pin.metadata.name)
-                .description(pin.metadata.description)
-                .labels(pin.metadata.labels.into_iter().map(Into::into).collect())
-                .tags(pin.metadata.tags)
-                .into_message()?,
-        )
-        .await?;
+    let msg = create_pin_msg(pin, me)?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    let resp = client.pins.create(msg).await?;
 
     Ok(serde_json::json!({
         "status": "success",
@@ -179,6 +398,153 @@ async fn create_pin(
     }))
 }
 
+/// Adds the file or directory and creates the pin for its CID
+async fn add_pin(
+    chain_args: &ChainArgs,
+    add_args: &AddArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let path = &add_args.path;
+    let dag = if let Some(car) = &add_args.car {
+        let mut out = std::io::BufWriter::new(std::fs::File::create(car)?);
+        unixfs::write_car(path, add_args.hidden, &mut out)?
+    } else {
+        unixfs::import(path, add_args.hidden)?
+    };
+    let cid = dag.cid.to_string();
+    let mut value = serde_json::json!({
+        "cid": cid,
+        "bytes": dag.size,
+        "size": dag.content_size,
+    });
+    if let Some(car) = &add_args.car {
+        value["car"] = car.display().to_string().into();
+    }
+    if add_args.only_hash {
+        return Ok(value);
+    }
+
+    if let Some(ipfs_api) = &add_args.ipfs_api {
+        if let Some(car) = &add_args.car {
+            import_car(ipfs_api, car, &cid).await?;
+        } else {
+            add_to_ipfs(ipfs_api, path, add_args.hidden).await?;
+        }
+        value["ipfs"] = ipfs_api.clone().into();
+    }
+
+    let name = match &add_args.name {
+        Some(name) => name.clone(),
+        None => path
+            .canonicalize()?
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let pin = serde_json::json!({
+        "kind": "Pin",
+        "version": "v0",
+        "metadata": {
+            "name": name,
+            "description": add_args.description,
+            "tags": [],
+            "labels": [],
+        },
+        "spec": {
+            "cid": cid,
+            "bytes": dag.content_size,
+            "time": add_args.time,
+            "redundancy": add_args.redundancy,
+            "fallbackUrls": add_args.fallback_urls,
+        },
+    });
+    let created = create_pin(chain_args, serde_json::from_value(pin)?).await?;
+    if !chain_args.signs_transactions() {
+        return Ok(created);
+    }
+    if let (Value::Object(value), Value::Object(created)) = (&mut value, created) {
+        value.extend(created);
+    }
+    Ok(value)
+}
+
+/// Adds the file or directory into IPFS node through its HTTP API and returns its CID.
+pub(crate) async fn add_to_ipfs(
+    ipfs_api: &str,
+    path: &Path,
+    hidden: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let tmp = tempdir::TempDir::new("gvltctl-pin")?;
+    let car = tmp.path().join("dag.car");
+    let mut out = std::io::BufWriter::new(std::fs::File::create(&car)?);
+    let cid = unixfs::write_car(path, hidden, &mut out)?.cid.to_string();
+    drop(out);
+    import_car(ipfs_api, &car, &cid).await?;
+    Ok(cid)
+}
+
+/// Imports the CAR file into IPFS node through its HTTP API and pins its root
+async fn import_car(
+    ipfs_api: &str,
+    car: &Path,
+    cid: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = tokio::fs::File::open(car).await?;
+    let length = file.metadata().await?.len();
+    let part = reqwest::multipart::Part::stream_with_length(file, length)
+        .file_name("dag.car")
+        .mime_str("application/vnd.ipld.car")?;
+    let form = reqwest::multipart::Form::new().part("file", part);
+    let url = format!(
+        "{}/api/v0/dag/import?pin-roots=true",
+        ipfs_api.trim_end_matches('/')
+    );
+    let response = reqwest::Client::new()
+        .post(url)
+        .multipart(form)
+        .send()
+        .await?;
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(format!("IPFS API error ({}): {}", status, body.trim()).into());
+    }
+    // The response is a stream of JSON objects, failed pins of roots are reported in them
+    for line in body.lines().filter(|line| !line.trim().is_empty()) {
+        let event: Value = serde_json::from_str(line)?;
+        if let Some(err) = event["Root"]["PinErrorMsg"]
+            .as_str()
+            .filter(|err| !err.is_empty())
+        {
+            return Err(format!("failed to pin {} in IPFS: {}", cid, err).into());
+        }
+    }
+    Ok(())
+}
+
+/// Builds the message creating the pin
+pub fn create_pin_msg(
+    pin: gevulot_rs::models::Pin,
+    creator: String,
+) -> Result<MsgCreatePin, Box<dyn std::error::Error>> {
+    // Create the pin using the MsgCreatePinBuilder
+    let msg = MsgCreatePinBuilder::default()
+        .creator(creator)
+        .cid(pin.spec.cid.clone())
+        .fallback_urls(pin.spec.fallback_urls.unwrap_or_default())
+        .bytes(ByteSize::new(
+            pin.spec.bytes.bytes()? as u64,
+            ByteUnit::Byte,
+        ))
+        .time(pin.spec.time.seconds()? as u64)
+        .redundancy(pin.spec.redundancy as u64)
+        .name(pin.metadata.name)
+        .description(pin.metadata.description)
+        .labels(pin.metadata.labels.into_iter().map(Into::into).collect())
+        .tags(pin.metadata.tags)
+        .into_message()?;
+    Ok(msg)
+}
+
 /// Deletes a pin from the Gevulot network
 async fn delete_pin(
     chain_args: &ChainArgs,
@@ -187,27 +553,52 @@ async fn delete_pin(
     let mut client = connect_to_gevulot(chain_args).await?;
 
     // Get the client's address
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
+    let me = signer_address(chain_args, &client).await?;
 
     // Delete the pin using the MsgDeletePinBuilder
-    client
-        .pins
-        .delete(
-            MsgDeletePinBuilder::default()
-                .creator(me.clone())
-                .cid(pin_cid.to_string())
-                .into_message()?,
-        )
-        .await?;
+    let msg = MsgDeletePinBuilder::default()
+        .creator(me.clone())
+        .cid(pin_cid.to_string())
+        .into_message()?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.pins.delete(msg).await?;
 
     Ok(serde_json::json!({
         "status": "success",
         "message": format!("Deleted pin with CID: {}", pin_cid)
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replication_status() {
+        let pin = serde_json::json!({
+            "metadata": { "id": "pin-1" },
+            "spec": { "cid": "bafy", "redundancy": 2 },
+            "status": {
+                "assignedWorkers": ["w1", "w2", "w3"],
+                "workerAcks": [
+                    { "worker": "w1", "success": true },
+                    { "worker": "w2", "success": false, "error": "no space" },
+                ],
+            },
+        });
+        let status = replication_status(&pin);
+        assert_eq!(status["acked"], 1);
+        assert_eq!(status["failed"], 1);
+        assert_eq!(status["pending"], 1);
+        assert_eq!(status["replicated"], false);
+        assert_eq!(status["workers"][1]["error"], "no space");
+
+        let mut pin = pin;
+        pin["status"]["workerAcks"][1] = serde_json::json!({ "worker": "w3", "success": true });
+        let status = replication_status(&pin);
+        assert_eq!(status["acked"], 2);
+        assert_eq!(status["replicated"], true);
+    }
+}
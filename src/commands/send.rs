@@ -0,0 +1,341 @@
+//! Token transfers.
+
+use cosmrs::proto::cosmos::bank::v1beta1::query_client::QueryClient as BankQueryClient;
+use cosmrs::proto::cosmos::bank::v1beta1::{Metadata, MsgSend, QueryDenomsMetadataRequest};
+use cosmrs::proto::cosmos::base::query::v1beta1::PageRequest;
+use cosmrs::proto::cosmos::base::v1beta1::Coin;
+use cosmrs::{AccountId, Any};
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::commands::tx;
+use crate::utils::{confirm, grpc_endpoint};
+use crate::{connect_to_gevulot, ChainArgs};
+
+/// Token amount as entered by the user, e.g. `1.5gvlt` or `1500000ugvlt`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenAmount {
+    /// Decimal number.
+    number: String,
+
+    /// Denomination.
+    denom: String,
+}
+
+impl FromStr for TokenAmount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, denom) = s.split_at(split);
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+        let is_valid_number = !(integer.is_empty() && fraction.is_empty())
+            && integer.bytes().all(|b| b.is_ascii_digit())
+            && fraction.bytes().all(|b| b.is_ascii_digit());
+        if !is_valid_number {
+            return Err(format!("invalid amount '{}', expected e.g. 1.5gvlt", s));
+        }
+        let denom = denom.trim();
+        if denom.is_empty() {
+            return Err(format!(
+                "amount '{}' has no denomination, expected e.g. 1.5gvlt",
+                s
+            ));
+        }
+        let is_valid_denom = denom.starts_with(|c: char| c.is_ascii_alphabetic())
+            && denom
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | ':' | '.' | '_' | '-'));
+        if !is_valid_denom {
+            return Err(format!("invalid denomination '{}'", denom));
+        }
+        Ok(Self {
+            number: number.to_string(),
+            denom: denom.to_string(),
+        })
+    }
+}
+
+impl TokenAmount {
+    /// Converts the amount into base units using metadata of denominations.
+    ///
+    /// Returns the base denomination and the amount in it.
+    /// Denominations without metadata are rejected unless `raw_denom` is set,
+    /// then they are treated as base denominations.
+    fn to_base_units(
+        &self,
+        metadatas: &[Metadata],
+        raw_denom: bool,
+    ) -> Result<(String, u128), String> {
+        let denom = self.denom.as_str();
+        for metadata in metadatas {
+            let unit = metadata.denom_units.iter().find(|unit| {
+                unit.denom.eq_ignore_ascii_case(denom)
+                    || unit
+                        .aliases
+                        .iter()
+                        .any(|alias| alias.eq_ignore_ascii_case(denom))
+            });
+            if let Some(unit) = unit {
+                let amount = scale(&self.number, unit.exponent)
+                    .map_err(|err| format!("{}{}: {}", self.number, denom, err))?;
+                return Ok((metadata.base.clone(), amount));
+            }
+        }
+        if !raw_denom {
+            return Err(format!(
+                "denomination '{}' has no metadata on chain, use --raw-denom to send it as is",
+                denom
+            ));
+        }
+        let amount = scale(&self.number, 0).map_err(|err| {
+            format!(
+                "{}{}: {} (denomination '{}' has no metadata, so the amount must be in base units)",
+                self.number, denom, err, denom
+            )
+        })?;
+        Ok((denom.to_string(), amount))
+    }
+}
+
+/// Multiplies decimal number by 10^`exponent`. The result must be an integer.
+fn scale(number: &str, exponent: u32) -> Result<u128, String> {
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > exponent as usize {
+        return Err(format!("too many decimal places (at most {})", exponent));
+    }
+    let digits = format!(
+        "{}{:0<width$}",
+        integer,
+        fraction,
+        width = exponent as usize
+    );
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    digits
+        .parse()
+        .map_err(|_| "amount is too large".to_string())
+}
+
+/// Formats amount in base units as decimal number in units with `exponent`.
+fn format_units(amount: u128, exponent: u32) -> String {
+    let exponent = exponent as usize;
+    let digits = format!("{:0>width$}", amount, width = exponent + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - exponent);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Describes the amount in base units and, if metadata is known, in display units.
+fn describe_amount(denom: &str, amount: u128, metadatas: &[Metadata]) -> String {
+    let display = metadatas
+        .iter()
+        .find(|metadata| metadata.base == denom)
+        .and_then(|metadata| {
+            metadata
+                .denom_units
+                .iter()
+                .find(|unit| unit.denom == metadata.display && unit.exponent > 0)
+        });
+    match display {
+        Some(unit) => format!(
+            "{}{} ({} {})",
+            amount,
+            denom,
+            format_units(amount, unit.exponent),
+            unit.denom
+        ),
+        None => format!("{}{}", amount, denom),
+    }
+}
+
+/// Checks that the receiver is a valid bech32 address with the same prefix as the sender.
+fn check_receiver(receiver: &str, sender: &str) -> Result<(), String> {
+    let receiver_id = receiver
+        .parse::<AccountId>()
+        .map_err(|err| format!("invalid receiver address '{}': {}", receiver, err))?;
+    let sender_id = sender
+        .parse::<AccountId>()
+        .map_err(|err| format!("invalid sender address '{}': {}", sender, err))?;
+    if receiver_id.prefix() != sender_id.prefix() {
+        return Err(format!(
+            "receiver address '{}' has prefix '{}', expected '{}'",
+            receiver,
+            receiver_id.prefix(),
+            sender_id.prefix()
+        ));
+    }
+    Ok(())
+}
+
+/// Queries metadata of all denominations, page by page.
+async fn denoms_metadata(
+    chain_args: &ChainArgs,
+) -> Result<Vec<Metadata>, Box<dyn std::error::Error>> {
+    let mut bank = BankQueryClient::connect(grpc_endpoint(chain_args)).await?;
+    let mut metadatas = vec![];
+    let mut key = vec![];
+    loop {
+        let response = bank
+            .denoms_metadata(QueryDenomsMetadataRequest {
+                pagination: Some(PageRequest {
+                    key,
+                    ..Default::default()
+                }),
+            })
+            .await?
+            .into_inner();
+        metadatas.extend(response.metadatas);
+        match response.pagination {
+            Some(page) if !page.next_key.is_empty() => key = page.next_key,
+            _ => return Ok(metadatas),
+        }
+    }
+}
+
+/// Sends tokens to the receiver after confirmation.
+///
+/// The amount is converted into base units using denomination metadata of the chain.
+/// The user is asked to confirm the transfer with its fee unless `yes` is set.
+pub async fn send_tokens(
+    chain_args: &ChainArgs,
+    amount: &TokenAmount,
+    raw_denom: bool,
+    receiver: &str,
+    memo: &str,
+    yes: bool,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let chain_args = chain_args.resolve()?;
+    let client = connect_to_gevulot(&chain_args).await?;
+    let sender = tx::signer_address(&chain_args, &client).await?;
+    check_receiver(receiver, &sender)?;
+
+    let metadatas = denoms_metadata(&chain_args).await?;
+    let (denom, base_amount) = amount.to_base_units(&metadatas, raw_denom)?;
+    if base_amount == 0 {
+        return Err("amount must be positive".into());
+    }
+
+    let msg = MsgSend {
+        from_address: sender,
+        to_address: receiver.to_string(),
+        amount: vec![Coin {
+            denom: denom.clone(),
+            amount: base_amount.to_string(),
+        }],
+    };
+    if let Some(value) = tx::intercept_with_memo(&chain_args, &client, &msg, memo).await? {
+        return Ok(value);
+    }
+
+    let unsigned = tx::generate(&chain_args, &client, vec![Any::from_msg(&msg)?], memo).await?;
+    let fee = format!("{}{}", unsigned.fee.amount, unsigned.fee.denom);
+    if !yes {
+        eprintln!(
+            "Amount:   {}",
+            describe_amount(&denom, base_amount, &metadatas)
+        );
+        eprintln!("Receiver: {}", receiver);
+        eprintln!("Fee:      {}", fee);
+        if !memo.is_empty() {
+            eprintln!("Memo:     {}", memo);
+        }
+        if !confirm("Send tokens?")? {
+            return Err("aborted".into());
+        }
+    }
+    let hash = tx::sign_and_send(&chain_args, &client, &unsigned).await?;
+
+    Ok(serde_json::json!({
+        "success": true,
+        "amount": base_amount,
+        "denom": denom,
+        "receiver": receiver,
+        "fee": fee,
+        "memo": memo,
+        "hash": hash,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmrs::proto::cosmos::bank::v1beta1::DenomUnit;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            base: "ugvlt".to_string(),
+            display: "gvlt".to_string(),
+            denom_units: vec![
+                DenomUnit {
+                    denom: "ugvlt".to_string(),
+                    exponent: 0,
+                    aliases: vec!["microgvlt".to_string()],
+                },
+                DenomUnit {
+                    denom: "gvlt".to_string(),
+                    exponent: 6,
+                    aliases: vec![],
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_to_base_units() {
+        let metadatas = [metadata()];
+        let base = |s: &str, raw_denom: bool| {
+            s.parse::<TokenAmount>()
+                .unwrap()
+                .to_base_units(&metadatas, raw_denom)
+        };
+
+        assert_eq!(base("1.5gvlt", false), Ok(("ugvlt".to_string(), 1_500_000)));
+        assert_eq!(
+            base("1500000ugvlt", false),
+            Ok(("ugvlt".to_string(), 1_500_000))
+        );
+        assert_eq!(base("2 microgvlt", false), Ok(("ugvlt".to_string(), 2)));
+        assert!(base("0.0000001gvlt", false).is_err());
+        assert!(base("1.5ugvlt", false).is_err());
+        assert!(base("42ufoo", false).is_err());
+        assert_eq!(base("42ufoo", true), Ok(("ufoo".to_string(), 42)));
+        assert!(base("1.5ufoo", true).is_err());
+
+        assert!("42".parse::<TokenAmount>().is_err());
+        assert!("gvlt".parse::<TokenAmount>().is_err());
+        assert!("1.2.3gvlt".parse::<TokenAmount>().is_err());
+        assert!("1.5 g$vlt".parse::<TokenAmount>().is_err());
+    }
+
+    #[test]
+    fn test_describe_amount() {
+        let metadatas = [metadata()];
+        assert_eq!(
+            describe_amount("ugvlt", 1_500_000, &metadatas),
+            "1500000ugvlt (1.5 gvlt)"
+        );
+        assert_eq!(describe_amount("ucredit", 7, &metadatas), "7ucredit");
+    }
+
+    #[test]
+    fn test_check_receiver() {
+        let sender = AccountId::new("gvlt", &[1; 20]).unwrap().to_string();
+        let receiver = AccountId::new("gvlt", &[2; 20]).unwrap().to_string();
+        let other = AccountId::new("cosmos", &[2; 20]).unwrap().to_string();
+        assert!(check_receiver(&receiver, &sender).is_ok());
+        assert!(check_receiver(&other, &sender).is_err());
+        assert!(check_receiver("gvlt1invalid", &sender).is_err());
+    }
+}
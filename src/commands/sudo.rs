@@ -3,7 +3,8 @@ use gevulot_rs::proto::gevulot::gevulot::{
 };
 use serde_json::Value;
 
-use crate::{connect_to_gevulot, print_object, ChainArgs, OutputFormat};
+use crate::commands::tx::{intercept, signer_address};
+use crate::{connect_to_gevulot, print_object, ChainArgs, OutputArgs};
 
 /// Sudo command.
 #[derive(Clone, Debug, clap::Parser)]
@@ -17,18 +18,17 @@ pub struct Command {
 
 impl Command {
     /// Match sudo subcommand and run it.
-    pub async fn run(&self, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let chain_args = &self.chain_args.unlock()?;
         let value = match &self.subcommand {
-            Subcommand::DeletePin { id } => sudo_delete_pin(&self.chain_args, id.clone()).await,
-            Subcommand::DeleteWorker { id } => {
-                sudo_delete_worker(&self.chain_args, id.clone()).await
-            }
-            Subcommand::DeleteTask { id } => sudo_delete_task(&self.chain_args, id.clone()).await,
+            Subcommand::DeletePin { id } => sudo_delete_pin(chain_args, id.clone()).await,
+            Subcommand::DeleteWorker { id } => sudo_delete_worker(chain_args, id.clone()).await,
+            Subcommand::DeleteTask { id } => sudo_delete_task(chain_args, id.clone()).await,
             Subcommand::FreezeAccount { address } => {
-                sudo_freeze_account(&self.chain_args, address.clone()).await
+                sudo_freeze_account(chain_args, address.clone()).await
             }
         }?;
-        print_object(format, &value)
+        print_object(output, &value)
     }
 }
 
@@ -67,9 +67,12 @@ async fn sudo_delete_pin(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
     let msg = MsgSudoDeletePin {
-        authority: client.base_client.write().await.address.clone().unwrap(),
+        authority: signer_address(chain_args, &client).await?,
         cid,
     };
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
     client.sudo.delete_pin(msg).await?;
     Ok(serde_json::json!({
         "status": "success",
@@ -84,9 +87,12 @@ async fn sudo_delete_worker(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
     let msg = MsgSudoDeleteWorker {
-        authority: client.base_client.write().await.address.clone().unwrap(),
+        authority: signer_address(chain_args, &client).await?,
         id: worker_id,
     };
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
     client.sudo.delete_worker(msg).await?;
     Ok(serde_json::json!({
         "status": "success",
@@ -101,9 +107,12 @@ async fn sudo_delete_task(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
     let msg = MsgSudoDeleteTask {
-        authority: client.base_client.write().await.address.clone().unwrap(),
+        authority: signer_address(chain_args, &client).await?,
         id: task_id,
     };
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
     client.sudo.delete_task(msg).await?;
     Ok(serde_json::json!({
         "status": "success",
@@ -118,9 +127,12 @@ async fn sudo_freeze_account(
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
     let msg = MsgSudoFreezeAccount {
-        authority: client.base_client.write().await.address.clone().unwrap(),
+        authority: signer_address(chain_args, &client).await?,
         account,
     };
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
     client.sudo.freeze_account(msg).await?;
     Ok(serde_json::json!({
         "status": "success",
@@ -1,14 +1,32 @@
+use cosmrs::Any;
+use downloader::{Download, Downloader};
 use patharg::InputArg;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::Path;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use gevulot_rs::builders::{
     ByteSize, ByteUnit, MsgAcceptTaskBuilder, MsgCreateTaskBuilder, MsgDeclineTaskBuilder,
     MsgFinishTaskBuilder, MsgRescheduleTaskBuilder,
 };
+use gevulot_rs::proto::gevulot::gevulot::{
+    MsgCreateTask, MsgCreateTaskResponse, MsgDeleteTask, QueryAllTaskRequest,
+};
+use gevulot_rs::GevulotClient;
 
-use crate::{connect_to_gevulot, print_object, read_file, ChainArgs, OutputFormat};
+use crate::commands::batch::{self, BatchArgs, BatchMessage};
+use crate::commands::local_run::{
+    is_plain_relative, mount_relative_path, GEVULOT_OUTPUT_MOUNTPOINT,
+};
+use crate::commands::tx::{self, intercept, signer_address};
+use crate::commands::validate;
+use crate::template::TemplateArgs;
+use crate::utils::{confirm, Document};
+use crate::{
+    connect_to_gevulot, parse_key_value, print_object, ChainArgs, ListArgs, OutputArgs, WaitArgs,
+};
 
 /// Tasks command.
 #[derive(Clone, Debug, clap::Parser)]
@@ -22,19 +40,122 @@ pub struct Command {
 
 impl Command {
     /// Match task subcommand and run it.
-    pub async fn run(&self, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        if let Subcommand::Create {
+            file,
+            validate_only: true,
+            template_args,
+            ..
+        } = &self.subcommand
+        {
+            let path = file.path_ref().map(|v| &**v);
+            let problems = validate::validate_input(path, template_args).await?;
+            return validate::report(output, &problems);
+        }
+
+        let chain_args = &self.chain_args.resolve_for(self.subcommand.signs())?;
+
+        if let Subcommand::Logs {
+            id,
+            stderr,
+            follow,
+            wait_args,
+            output_file,
+            ipfs_gateway,
+        } = &self.subcommand
+        {
+            let stream = if *stderr { "stderr" } else { "stdout" };
+            let wait_args = follow.then_some(wait_args);
+            let logs = task_logs(chain_args, id, stream, wait_args, ipfs_gateway).await?;
+            return match output_file {
+                Some(path) => {
+                    tokio::fs::write(path, &logs).await?;
+                    print_object(
+                        output,
+                        &serde_json::json!({
+                            "status": "success",
+                            "file": path,
+                            "bytes": logs.len(),
+                        }),
+                    )
+                }
+                None => Ok(std::io::stdout().write_all(&logs)?),
+            };
+        }
+
         let value = match &self.subcommand {
-            Subcommand::List => list_tasks(&self.chain_args).await,
-            Subcommand::Get { id } => get_task(&self.chain_args, id).await,
-            Subcommand::Create { file } => {
-                create_task(&self.chain_args, file.path_ref().map(|v| &**v)).await
-            }
-            Subcommand::Accept { id, worker_id } => {
-                accept_task(&self.chain_args, id, worker_id).await
+            Subcommand::List { list_args } => list_tasks(chain_args, list_args).await,
+            Subcommand::Get { id } => get_task(chain_args, id).await,
+            Subcommand::Render {
+                file,
+                template_args,
+            } => render_tasks(file.path_ref().map(|v| &**v), template_args).await,
+            Subcommand::Estimate {
+                file,
+                template_args,
+                pricing_args,
+                batch_args,
+            } => {
+                let documents = template_args
+                    .read_documents(file.path_ref().map(|v| &**v))
+                    .await?;
+                let client = connect_to_gevulot(chain_args).await?;
+                estimate_tasks(chain_args, &client, &documents, pricing_args, batch_args).await
             }
-            Subcommand::Decline { id, worker_id } => {
-                decline_task(&self.chain_args, id, worker_id).await
+            Subcommand::Create {
+                file,
+                wait,
+                wait_args,
+                download_outputs,
+                output_dir,
+                ipfs_gateway,
+                batch_args,
+                template_args,
+                force,
+                pricing_args,
+                ..
+            } => {
+                let path = file.path_ref().map(|v| &**v);
+                let mut documents = template_args.read_documents(path).await?;
+                if documents.is_empty() {
+                    return Err("no task documents found".into());
+                }
+                let mut client = connect_to_gevulot(chain_args).await?;
+                if !force && chain_args.signs_transactions() {
+                    check_budget(chain_args, &client, &documents, pricing_args, batch_args).await?;
+                }
+                if documents.len() > 1 || path.is_some_and(Path::is_dir) {
+                    if *wait {
+                        return Err("--wait can only be used with a single task".into());
+                    }
+                    batch::create_all(
+                        chain_args,
+                        &client,
+                        documents,
+                        batch_args,
+                        create_task_msg,
+                        |resp: MsgCreateTaskResponse| resp.id,
+                    )
+                    .await
+                } else {
+                    let task = serde_yaml::from_str(&documents.remove(0).content)?;
+                    if *wait {
+                        create_task_and_wait(
+                            chain_args,
+                            &mut client,
+                            task,
+                            wait_args,
+                            download_outputs.then_some(output_dir.as_path()),
+                            ipfs_gateway,
+                        )
+                        .await
+                    } else {
+                        create_task(chain_args, &mut client, task).await
+                    }
+                }
             }
+            Subcommand::Accept { id, worker_id } => accept_task(chain_args, id, worker_id).await,
+            Subcommand::Decline { id, worker_id } => decline_task(chain_args, id, worker_id).await,
             Subcommand::Finish {
                 id,
                 exit_code,
@@ -44,7 +165,7 @@ impl Command {
                 output_contexts,
             } => {
                 finish_task(
-                    &self.chain_args,
+                    chain_args,
                     id,
                     *exit_code,
                     stdout.as_ref(),
@@ -54,10 +175,43 @@ impl Command {
                 )
                 .await
             }
-            Subcommand::Reschedule { id } => reschedule_task(&self.chain_args, id).await,
-            Subcommand::Delete { id } => delete_task(&self.chain_args, id).await,
+            Subcommand::Reschedule { id: Some(id), .. } => reschedule_task(chain_args, id).await,
+            Subcommand::Reschedule {
+                id: None,
+                selector_args,
+            } => {
+                bulk_task_operation(chain_args, selector_args, "reschedule", |me, id| {
+                    let msg = MsgRescheduleTaskBuilder::default()
+                        .creator(me)
+                        .task_id(id.clone())
+                        .into_message()?;
+                    BatchMessage::with_id(&msg, "reschedule", id)
+                })
+                .await
+            }
+            Subcommand::Delete { id: Some(id), .. } => delete_task(chain_args, id).await,
+            Subcommand::Delete {
+                id: None,
+                selector_args,
+            } => {
+                bulk_task_operation(chain_args, selector_args, "delete", |me, id| {
+                    let msg = MsgDeleteTask {
+                        creator: me,
+                        id: id.clone(),
+                    };
+                    BatchMessage::with_id(&msg, "delete", id)
+                })
+                .await
+            }
+            Subcommand::Wait { id, wait_args } => wait_task(chain_args, id, wait_args).await,
         }?;
-        print_object(format, &value)
+        print_object(output, &value)?;
+        match &self.subcommand {
+            Subcommand::Wait { .. } => exit_with_task_code(&value),
+            Subcommand::Create { wait: true, .. } => exit_with_task_code(&value["task"]),
+            _ => {}
+        }
+        Ok(())
     }
 }
 
@@ -65,7 +219,10 @@ impl Command {
 #[derive(Clone, Debug, clap::Subcommand)]
 enum Subcommand {
     /// List all tasks.
-    List,
+    List {
+        #[command(flatten)]
+        list_args: ListArgs,
+    },
 
     /// Get a specific task.
     Get {
@@ -74,10 +231,92 @@ enum Subcommand {
     },
 
     /// Create a new task.
+    ///
+    /// Multiple tasks can be created at once from a multi-document YAML file
+    /// or from a directory of task files. They are submitted in batches.
     Create {
-        /// The file to read the task data from or '-' to read from stdin.
+        /// The file or directory to read the task data from or '-' to read from stdin.
+        #[arg(short, long, default_value_t)]
+        file: InputArg,
+
+        /// Only validate the task file without creating the task.
+        #[arg(long, conflicts_with = "wait")]
+        validate_only: bool,
+
+        /// Wait for the created task to reach a terminal state.
+        ///
+        /// The process exits with the exit code of the task.
+        #[arg(long)]
+        wait: bool,
+
+        #[command(flatten)]
+        wait_args: WaitArgs,
+
+        /// Download stored stdout/stderr and output contexts of the finished task.
+        #[arg(long, requires = "wait")]
+        download_outputs: bool,
+
+        /// Directory to store downloaded outputs.
+        ///
+        /// The layout is the same as the one produced by `local-run`.
+        #[arg(long, value_name = "DIR", default_value = "output")]
+        output_dir: PathBuf,
+
+        /// IPFS gateway used to download output contexts.
+        #[arg(
+            long,
+            value_name = "URL",
+            value_hint = clap::ValueHint::Url,
+            env = "GEVULOT_IPFS_GATEWAY",
+            default_value = "https://ipfs.io/ipfs"
+        )]
+        ipfs_gateway: String,
+
+        #[command(flatten)]
+        batch_args: BatchArgs,
+
+        #[command(flatten)]
+        template_args: TemplateArgs,
+
+        /// Submit tasks without checking that the account balance covers their estimated cost.
+        ///
+        /// The check requires resource prices, so it must be skipped if they are not set.
+        #[arg(long)]
+        force: bool,
+
+        #[command(flatten)]
+        pricing_args: PricingArgs,
+    },
+
+    /// Estimate the cost of tasks without creating them.
+    ///
+    /// The cost consists of the escrow for task resources and the transaction fee.
+    /// It is compared with the balance of the signer account.
+    Estimate {
+        /// The file or directory to read the task data from or '-' to read from stdin.
+        #[arg(short, long, default_value_t)]
+        file: InputArg,
+
+        #[command(flatten)]
+        template_args: TemplateArgs,
+
+        #[command(flatten)]
+        pricing_args: PricingArgs,
+
+        #[command(flatten)]
+        batch_args: BatchArgs,
+    },
+
+    /// Render templated task files without creating tasks.
+    ///
+    /// Prints tasks with all variables substituted.
+    Render {
+        /// The file or directory to read the task data from or '-' to read from stdin.
         #[arg(short, long, default_value_t)]
         file: InputArg,
+
+        #[command(flatten)]
+        template_args: TemplateArgs,
     },
 
     /// Accept a task (you probably should not use this).
@@ -123,25 +362,232 @@ enum Subcommand {
         output_contexts: Option<Vec<String>>,
     },
 
-    /// Reschedule a task.
+    /// Reschedule a task or all own tasks matching the selector.
     Reschedule {
         /// The ID of the task to reschedule.
-        id: String,
+        #[arg(required_unless_present_any = ["selector", "state"])]
+        id: Option<String>,
+
+        #[command(flatten)]
+        selector_args: SelectorArgs,
     },
 
-    /// Delete a task.
+    /// Delete a task or all own tasks matching the selector.
     Delete {
         /// The ID of the task to delete.
+        #[arg(required_unless_present_any = ["selector", "state"])]
+        id: Option<String>,
+
+        #[command(flatten)]
+        selector_args: SelectorArgs,
+    },
+
+    /// Wait for a task to reach a terminal state.
+    ///
+    /// State transitions are reported to stderr. When the task is finished,
+    /// it is printed and the process exits with the exit code of the task.
+    Wait {
+        /// The ID of the task to wait for.
         id: String,
+
+        #[command(flatten)]
+        wait_args: WaitArgs,
     },
+
+    /// Print stored stdout or stderr of a finished task.
+    ///
+    /// The task must be created with `storeStdout` or `storeStderr` enabled.
+    Logs {
+        /// The ID of the task.
+        id: String,
+
+        /// Print stderr instead of stdout.
+        #[arg(long)]
+        stderr: bool,
+
+        /// Wait for the task to finish if it is still running.
+        #[arg(short, long)]
+        follow: bool,
+
+        #[command(flatten)]
+        wait_args: WaitArgs,
+
+        /// Write the output to the file instead of stdout.
+        #[arg(short, long, value_name = "FILE")]
+        output_file: Option<PathBuf>,
+
+        /// IPFS gateway used to download output stored in IPFS.
+        #[arg(
+            long,
+            value_name = "URL",
+            value_hint = clap::ValueHint::Url,
+            env = "GEVULOT_IPFS_GATEWAY",
+            default_value = "https://ipfs.io/ipfs"
+        )]
+        ipfs_gateway: String,
+    },
+}
+
+impl Subcommand {
+    /// Returns `true` if the subcommand signs transactions.
+    fn signs(&self) -> bool {
+        !matches!(
+            self,
+            Subcommand::List { .. }
+                | Subcommand::Get { .. }
+                | Subcommand::Render { .. }
+                | Subcommand::Estimate { .. }
+                | Subcommand::Logs { .. }
+                | Subcommand::Wait { .. }
+        )
+    }
+}
+
+/// Arguments selecting tasks for bulk operations.
+///
+/// Only tasks created by the signer are selected.
+#[derive(Clone, Debug, clap::Args)]
+struct SelectorArgs {
+    /// Select tasks with this label. Multiple labels can be separated with commas
+    /// or passed in multiple arguments, all of them must match.
+    ///
+    /// Example: --selector env=staging,sweep=42
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        value_delimiter = ',',
+        value_parser = parse_key_value,
+        conflicts_with = "id"
+    )]
+    selector: Vec<(String, String)>,
+
+    /// Select tasks in this state (e.g. pending, running, done, failed).
+    #[arg(long, conflicts_with = "id")]
+    state: Option<String>,
+
+    /// Do not ask for confirmation.
+    #[arg(short, long)]
+    yes: bool,
+
+    #[command(flatten)]
+    batch_args: BatchArgs,
+}
+
+/// Prices of task resources used to estimate the escrow of a task.
+///
+/// All prices are in the fee denomination per second of task time.
+/// Prices are not queried from the chain, so they must be set for every resource
+/// requested by the tasks.
+#[derive(Clone, Debug, clap::Args)]
+struct PricingArgs {
+    /// Price of one CPU per second.
+    #[arg(long, value_name = "PRICE", env = "GEVULOT_CPU_PRICE")]
+    cpu_price: Option<f64>,
+
+    /// Price of one GPU per second.
+    #[arg(long, value_name = "PRICE", env = "GEVULOT_GPU_PRICE")]
+    gpu_price: Option<f64>,
+
+    /// Price of one GiB of memory per second.
+    #[arg(long, value_name = "PRICE", env = "GEVULOT_MEMORY_PRICE")]
+    memory_price: Option<f64>,
+}
+
+impl PricingArgs {
+    /// Returns `true` if any of the prices is set.
+    fn is_set(&self) -> bool {
+        self.cpu_price.is_some() || self.gpu_price.is_some() || self.memory_price.is_some()
+    }
+
+    /// Returns the escrow for the resources.
+    ///
+    /// Fails if the price of a requested resource is not set.
+    fn escrow(&self, resources: &TaskResources) -> Result<u128, String> {
+        let cost = |amount: f64, price: Option<f64>, option: &str| match price {
+            _ if amount == 0.0 => Ok(0.0),
+            Some(price) => Ok(amount * price),
+            None => Err(format!("{} is required to estimate the escrow", option)),
+        };
+        let per_second = cost(
+            resources.cpus as f64 / 1000.0,
+            self.cpu_price,
+            "--cpu-price",
+        )? + cost(
+            resources.gpus as f64 / 1000.0,
+            self.gpu_price,
+            "--gpu-price",
+        )? + cost(
+            resources.memory as f64 / (1u64 << 30) as f64,
+            self.memory_price,
+            "--memory-price",
+        )?;
+        Ok((per_second * resources.time as f64).ceil() as u128)
+    }
+}
+
+/// Resources requested by a task in base units.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+struct TaskResources {
+    /// CPUs in millicores.
+    cpus: u64,
+
+    /// GPUs in millicores.
+    gpus: u64,
+
+    /// Memory in bytes.
+    memory: u64,
+
+    /// Time in seconds.
+    time: u64,
+}
+
+impl TaskResources {
+    /// Converts resources of the task specification into base units.
+    fn from_task(task: &gevulot_rs::models::Task) -> Result<Self, Box<dyn std::error::Error>> {
+        let resources = &task.spec.resources;
+        Ok(Self {
+            cpus: resources.cpus.millicores()? as u64,
+            gpus: resources.gpus.millicores()? as u64,
+            memory: resources.memory.bytes()? as u64,
+            time: resources.time.seconds()? as u64,
+        })
+    }
 }
 
 /// Lists all tasks.
-async fn list_tasks(chain_args: &ChainArgs) -> Result<Value, Box<dyn std::error::Error>> {
+async fn list_tasks(
+    chain_args: &ChainArgs,
+    list_args: &ListArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let tasks = client.tasks.list().await?;
-    let tasks: Vec<gevulot_rs::models::Task> = tasks.into_iter().map(Into::into).collect();
-    Ok(serde_json::json!(tasks))
+    let me = if list_args.mine {
+        Some(signer_address(chain_args, &client).await?)
+    } else {
+        None
+    };
+    let (tasks, list_args) = match list_args.page_request() {
+        Some(pagination) => {
+            let request = QueryAllTaskRequest {
+                pagination: Some(pagination),
+            };
+            let tasks = client
+                .base_client
+                .write()
+                .await
+                .gevulot_client
+                .task_all(request)
+                .await?
+                .into_inner()
+                .task;
+            (tasks, list_args.without_pagination())
+        }
+        None => (client.tasks.list().await?, list_args.clone()),
+    };
+    let tasks = tasks
+        .into_iter()
+        .map(|task| serde_json::json!(gevulot_rs::models::Task::from(task)))
+        .collect();
+    Ok(serde_json::json!(list_args.apply(tasks, me.as_deref())))
 }
 
 /// Retrieves and displays information for a specific task.
@@ -155,30 +601,162 @@ async fn get_task(
     Ok(serde_json::json!(task))
 }
 
+/// Estimates the cost of tasks and compares it with the balance of the signer.
+///
+/// The transaction fee is estimated by simulating transactions creating the tasks
+/// in batches of the same size as they are submitted.
+async fn estimate_tasks(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    documents: &[Document],
+    pricing_args: &PricingArgs,
+    batch_args: &BatchArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    if documents.is_empty() {
+        return Err("no task documents found".into());
+    }
+    let me = signer_address(chain_args, client).await?;
+
+    let mut tasks = vec![];
+    let mut messages = vec![];
+    let mut escrow = 0;
+    for document in documents {
+        let task: gevulot_rs::models::Task = serde_yaml::from_str(&document.content)
+            .map_err(|err| format!("{}:{}: {}", document.file, document.line, err))?;
+        let resources = TaskResources::from_task(&task)?;
+        let task_escrow = pricing_args
+            .escrow(&resources)
+            .map_err(|err| format!("{}:{}: {}", document.file, document.line, err))?;
+        escrow += task_escrow;
+        tasks.push(serde_json::json!({
+            "file": document.file,
+            "line": document.line,
+            "resources": resources,
+            "escrow": task_escrow,
+        }));
+        messages.push(Any::from_msg(&create_task_msg(task, me.clone())?)?);
+    }
+
+    let fee = tx::generate_batches(chain_args, client, messages, batch_args.batch_size)
+        .await?
+        .iter()
+        .map(|tx| tx.fee.amount)
+        .sum::<u128>();
+    let balance: u128 = client
+        .base_client
+        .write()
+        .await
+        .get_account_balance(&me)
+        .await?
+        .amount
+        .to_string()
+        .parse()?;
+    let total = escrow + fee;
+
+    Ok(serde_json::json!({
+        "tasks": tasks,
+        "denom": tx::FEE_DENOM,
+        "escrow": escrow,
+        "fee": fee,
+        "total": total,
+        "balance": balance,
+        "sufficient": balance >= total,
+    }))
+}
+
+/// Fails if the signer can't cover the estimated cost of the tasks.
+///
+/// Also fails if no resource prices are set, as the cost can't be estimated then.
+async fn check_budget(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    documents: &[Document],
+    pricing_args: &PricingArgs,
+    batch_args: &BatchArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !pricing_args.is_set() {
+        return Err(
+            "resource prices are not set, pass --cpu-price, --gpu-price and \
+             --memory-price to check the balance or use --force to skip the check"
+                .into(),
+        );
+    }
+    let estimate = estimate_tasks(chain_args, client, documents, pricing_args, batch_args).await?;
+    if estimate["sufficient"].as_bool() == Some(false) {
+        return Err(format!(
+            "account balance {balance}{denom} can't cover estimated cost {total}{denom} \
+             (escrow {escrow}{denom}, fee {fee}{denom}), use --force to submit anyway",
+            balance = estimate["balance"],
+            total = estimate["total"],
+            escrow = estimate["escrow"],
+            fee = estimate["fee"],
+            denom = tx::FEE_DENOM,
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Renders task documents and checks they are valid tasks.
+async fn render_tasks(
+    path: Option<&Path>,
+    template_args: &TemplateArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let template_args = TemplateArgs {
+        template: true,
+        ..template_args.clone()
+    };
+    let documents = template_args.read_documents(path).await?;
+    let mut tasks = documents
+        .iter()
+        .map(|document| {
+            serde_yaml::from_str::<gevulot_rs::models::Task>(&document.content)
+                .map_err(|err| format!("{}:{}: {}", document.file, document.line, err))?;
+            Ok(serde_yaml::from_str::<Value>(&document.content)?)
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    match tasks.len() {
+        0 => Err("no task documents found".into()),
+        1 => Ok(tasks.remove(0)),
+        _ => Ok(Value::Array(tasks)),
+    }
+}
+
 /// Creates a new task based on the provided specification.
 ///
 /// # Arguments
 ///
-/// * `_sub_m` - A reference to the ArgMatches struct containing parsed command-line arguments.
-///   This is used to read the task specification file, connect to Gevulot, and determine the output format.
+/// * `chain_args` - Chain arguments of the signer.
+/// * `client` - Connected Gevulot client used to submit the task.
+/// * `task` - The task to create.
 ///
 /// # Returns
 ///
 /// A Result indicating success or an error if the task creation fails.
 pub async fn create_task(
     chain_args: &ChainArgs,
-    path: Option<&Path>,
+    client: &mut GevulotClient,
+    task: gevulot_rs::models::Task,
 ) -> Result<Value, Box<dyn std::error::Error>> {
-    let task: gevulot_rs::models::Task = read_file(path).await?;
-    let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
+    let me = signer_address(chain_args, client).await?;
+    let msg = create_task_msg(task, me)?;
+    if let Some(value) = intercept(chain_args, client, &msg).await? {
+        return Ok(value);
+    }
+    let resp = client.tasks.create(msg).await?;
 
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": "Task created successfully",
+        "task_id": resp.id
+    }))
+}
+
+/// Builds the message creating the task.
+pub fn create_task_msg(
+    task: gevulot_rs::models::Task,
+    creator: String,
+) -> Result<MsgCreateTask, Box<dyn std::error::Error>> {
     let env: HashMap<String, String> = task
         .spec
         .env
@@ -200,42 +778,32 @@ pub async fn create_task(
         .map(|label| (label.key, label.value))
         .collect();
 
-    let resp = client
-        .tasks
-        .create(
-            MsgCreateTaskBuilder::default()
-                .creator(me.clone())
-                .image(task.spec.image)
-                .command(task.spec.command)
-                .args(task.spec.args)
-                .env(env)
-                .input_contexts(input_contexts)
-                .output_contexts(
-                    task.spec
-                        .output_contexts
-                        .into_iter()
-                        .map(|oc| (oc.source, oc.retention_period as u64))
-                        .collect(),
-                )
-                .cpus(task.spec.resources.cpus.millicores()? as u64)
-                .gpus(task.spec.resources.gpus.millicores()? as u64)
-                .memory(ByteSize::new(
-                    task.spec.resources.memory.bytes()? as u64,
-                    ByteUnit::Byte,
-                ))
-                .time(task.spec.resources.time.seconds()? as u64)
-                .store_stdout(task.spec.store_stdout)
-                .store_stderr(task.spec.store_stderr)
-                .labels(labels)
-                .into_message()?,
+    let msg = MsgCreateTaskBuilder::default()
+        .creator(creator)
+        .image(task.spec.image)
+        .command(task.spec.command)
+        .args(task.spec.args)
+        .env(env)
+        .input_contexts(input_contexts)
+        .output_contexts(
+            task.spec
+                .output_contexts
+                .into_iter()
+                .map(|oc| (oc.source, oc.retention_period as u64))
+                .collect(),
         )
-        .await?;
-
-    Ok(serde_json::json!({
-        "status": "success",
-        "message": "Task created successfully",
-        "task_id": resp.id
-    }))
+        .cpus(task.spec.resources.cpus.millicores()? as u64)
+        .gpus(task.spec.resources.gpus.millicores()? as u64)
+        .memory(ByteSize::new(
+            task.spec.resources.memory.bytes()? as u64,
+            ByteUnit::Byte,
+        ))
+        .time(task.spec.resources.time.seconds()? as u64)
+        .store_stdout(task.spec.store_stdout)
+        .store_stderr(task.spec.store_stderr)
+        .labels(labels)
+        .into_message()?;
+    Ok(msg)
 }
 
 pub async fn accept_task(
@@ -244,24 +812,17 @@ pub async fn accept_task(
     worker_id: &str,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
+    let me = signer_address(chain_args, &client).await?;
 
-    client
-        .tasks
-        .accept(
-            MsgAcceptTaskBuilder::default()
-                .creator(me.clone())
-                .task_id(task_id.to_string())
-                .worker_id(worker_id.to_string())
-                .into_message()?,
-        )
-        .await?;
+    let msg = MsgAcceptTaskBuilder::default()
+        .creator(me.clone())
+        .task_id(task_id.to_string())
+        .worker_id(worker_id.to_string())
+        .into_message()?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.tasks.accept(msg).await?;
     Ok(serde_json::json!({}))
 }
 
@@ -271,24 +832,17 @@ pub async fn decline_task(
     worker_id: &str,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
+    let me = signer_address(chain_args, &client).await?;
 
-    client
-        .tasks
-        .decline(
-            MsgDeclineTaskBuilder::default()
-                .creator(me.clone())
-                .task_id(task_id.to_string())
-                .worker_id(worker_id.to_string())
-                .into_message()?,
-        )
-        .await?;
+    let msg = MsgDeclineTaskBuilder::default()
+        .creator(me.clone())
+        .task_id(task_id.to_string())
+        .worker_id(worker_id.to_string())
+        .into_message()?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.tasks.decline(msg).await?;
     Ok(serde_json::json!({}))
 }
 
@@ -302,28 +856,21 @@ pub async fn finish_task(
     output_contexts: Option<&Vec<String>>,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
+    let me = signer_address(chain_args, &client).await?;
 
-    client
-        .tasks
-        .finish(
-            MsgFinishTaskBuilder::default()
-                .creator(me.clone())
-                .task_id(task_id.to_string())
-                .exit_code(exit_code)
-                .stdout(stdout.cloned())
-                .stderr(stderr.cloned())
-                .output_contexts(output_contexts.cloned())
-                .error(error.cloned())
-                .into_message()?,
-        )
-        .await?;
+    let msg = MsgFinishTaskBuilder::default()
+        .creator(me.clone())
+        .task_id(task_id.to_string())
+        .exit_code(exit_code)
+        .stdout(stdout.cloned())
+        .stderr(stderr.cloned())
+        .output_contexts(output_contexts.cloned())
+        .error(error.cloned())
+        .into_message()?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.tasks.finish(msg).await?;
     Ok(serde_json::json!({}))
 }
 
@@ -332,22 +879,15 @@ pub async fn reschedule_task(
     task_id: &str,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
-    let resp = client
-        .tasks
-        .reschedule(
-            MsgRescheduleTaskBuilder::default()
-                .creator(me.clone())
-                .task_id(task_id.to_string())
-                .into_message()?,
-        )
-        .await?;
+    let me = signer_address(chain_args, &client).await?;
+    let msg = MsgRescheduleTaskBuilder::default()
+        .creator(me.clone())
+        .task_id(task_id.to_string())
+        .into_message()?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    let resp = client.tasks.reschedule(msg).await?;
     Ok(serde_json::json!({
         "status": "success",
         "message": "Task rescheduled successfully",
@@ -361,24 +901,411 @@ pub async fn delete_task(
     task_id: &str,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
+    let me = signer_address(chain_args, &client).await?;
 
-    client
-        .tasks
-        .delete(gevulot_rs::proto::gevulot::gevulot::MsgDeleteTask {
-            creator: me.clone(),
-            id: task_id.to_string(),
-        })
-        .await?;
+    let msg = MsgDeleteTask {
+        creator: me.clone(),
+        id: task_id.to_string(),
+    };
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.tasks.delete(msg).await?;
 
     Ok(serde_json::json!({
         "status": "success",
         "message": "Task deleted successfully"
     }))
 }
+
+/// Maximum number of tasks listed in the confirmation summary.
+const SUMMARY_LIMIT: usize = 20;
+
+/// Applies the operation to all own tasks matching the selector.
+///
+/// Matching tasks are summarized and the user is asked for confirmation,
+/// then messages built by `build` from the signer address and the task ID
+/// are submitted in batches and every task is reported with its own result.
+async fn bulk_task_operation(
+    chain_args: &ChainArgs,
+    selector_args: &SelectorArgs,
+    action: &str,
+    build: impl Fn(String, String) -> Result<BatchMessage, Box<dyn std::error::Error>>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut client = connect_to_gevulot(chain_args).await?;
+    let me = signer_address(chain_args, &client).await?;
+    let list_args = ListArgs {
+        mine: true,
+        labels: selector_args.selector.clone(),
+        state: selector_args.state.clone(),
+        ..Default::default()
+    };
+    let tasks = client
+        .tasks
+        .list()
+        .await?
+        .into_iter()
+        .map(|task| serde_json::json!(gevulot_rs::models::Task::from(task)))
+        .collect();
+    let tasks = list_args.apply(tasks, Some(&me));
+    if tasks.is_empty() {
+        eprintln!("No tasks match the selector");
+        return Ok(serde_json::json!([]));
+    }
+
+    if chain_args.signs_transactions() && !selector_args.yes {
+        eprintln!("Tasks to {}:", action);
+        for task in tasks.iter().take(SUMMARY_LIMIT) {
+            eprintln!(
+                "  {}  {}  {}",
+                task["metadata"]["id"].as_str().unwrap_or_default(),
+                task_state(task).unwrap_or("Unknown"),
+                task["metadata"]["name"].as_str().unwrap_or_default(),
+            );
+        }
+        if tasks.len() > SUMMARY_LIMIT {
+            eprintln!("  ... and {} more", tasks.len() - SUMMARY_LIMIT);
+        }
+        if !confirm(&format!("{} {} tasks?", capitalize(action), tasks.len()))? {
+            return Err("aborted".into());
+        }
+    }
+
+    let entries = tasks
+        .iter()
+        .map(|task| {
+            let id = task["metadata"]["id"].as_str().unwrap_or_default();
+            let record = serde_json::json!({
+                "id": id,
+                "name": task["metadata"]["name"],
+                "state": task_state(task),
+            });
+            let msg = build(me.clone(), id.to_string()).map_err(|err| err.to_string());
+            (record, msg)
+        })
+        .collect();
+    batch::submit_all(chain_args, &client, entries, &selector_args.batch_args).await
+}
+
+/// Returns the string with the first letter in upper case.
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Task states after which task will not change anymore.
+const TERMINAL_TASK_STATES: [&str; 3] = ["done", "declined", "failed"];
+
+/// Returns the state of the task from its JSON representation.
+pub fn task_state(task: &Value) -> Option<&str> {
+    task.get("status")?.get("state")?.as_str()
+}
+
+/// Returns `true` if the task reached a terminal state.
+pub fn is_task_terminated(task: &Value) -> bool {
+    task_state(task).is_some_and(|state| {
+        TERMINAL_TASK_STATES
+            .iter()
+            .any(|terminal| state.eq_ignore_ascii_case(terminal))
+    })
+}
+
+/// Returns the exit code of the task from its JSON representation.
+pub fn task_exit_code(task: &Value) -> Option<i64> {
+    task.get("status")?.get("exitCode")?.as_i64()
+}
+
+/// Polls the task until it reaches a terminal state.
+///
+/// Every state transition is reported to stderr.
+async fn wait_task(
+    chain_args: &ChainArgs,
+    task_id: &str,
+    wait_args: &WaitArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut client = connect_to_gevulot(chain_args).await?;
+    let started = Instant::now();
+    let mut last_state: Option<String> = None;
+    loop {
+        let task: gevulot_rs::models::Task = client.tasks.get(task_id).await?.into();
+        let task = serde_json::json!(task);
+
+        let state = task_state(&task).unwrap_or("Unknown").to_string();
+        if last_state.as_ref() != Some(&state) {
+            match &last_state {
+                Some(previous) => eprintln!("Task {}: {} -> {}", task_id, previous, state),
+                None => eprintln!("Task {}: {}", task_id, state),
+            }
+            last_state = Some(state);
+        }
+
+        if is_task_terminated(&task) {
+            return Ok(task);
+        }
+
+        wait_args.check_timeout(started, &format!("task {}", task_id))?;
+
+        tokio::time::sleep(Duration::from_secs(wait_args.interval)).await;
+    }
+}
+
+/// Returns stored `stream` ("stdout" or "stderr") of the task.
+///
+/// If `wait_args` are set, waits for the task to finish first.
+/// Output stored as `ipfs://` reference is downloaded through the IPFS gateway.
+async fn task_logs(
+    chain_args: &ChainArgs,
+    task_id: &str,
+    stream: &str,
+    wait_args: Option<&WaitArgs>,
+    ipfs_gateway: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let task = match wait_args {
+        Some(wait_args) => wait_task(chain_args, task_id, wait_args).await?,
+        None => get_task(chain_args, task_id).await?,
+    };
+    if let Some(logs) = task["status"][stream].as_str() {
+        return resolve_logs(logs, ipfs_gateway).await;
+    }
+
+    let store_field = if stream == "stderr" {
+        "storeStderr"
+    } else {
+        "storeStdout"
+    };
+    if task["spec"][store_field].as_bool() == Some(false) {
+        Err(format!(
+            "task {} doesn't store its {} ({} is disabled)",
+            task_id, stream, store_field
+        )
+        .into())
+    } else if !is_task_terminated(&task) {
+        Err(format!(
+            "task {} is not finished yet, use --follow to wait for it",
+            task_id
+        )
+        .into())
+    } else {
+        Err(format!("task {} has no stored {}", task_id, stream).into())
+    }
+}
+
+/// Returns stored output, downloading it through the IPFS gateway if it is an `ipfs://` reference.
+async fn resolve_logs(
+    logs: &str,
+    ipfs_gateway: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    match logs.strip_prefix("ipfs://") {
+        Some(cid) => download_ipfs(cid, ipfs_gateway).await,
+        None => Ok(logs.as_bytes().to_vec()),
+    }
+}
+
+/// Downloads the file from IPFS gateway into memory.
+async fn download_ipfs(
+    cid: &str,
+    ipfs_gateway: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let tmp = tempdir::TempDir::new("gvltctl-logs")?;
+    let download = Download::new(&format!("{}/{}", ipfs_gateway.trim_end_matches('/'), cid))
+        .file_name(Path::new("output"));
+    let mut downloader = Downloader::builder().download_folder(tmp.path()).build()?;
+    let summary = downloader
+        .async_download(&[download])
+        .await?
+        .pop()
+        .ok_or(format!("failed to download '{}'", cid))?
+        .map_err(|err| format!("failed to download '{}': {}", cid, err))?;
+    match summary.status.first() {
+        Some((_, 200)) => Ok(tokio::fs::read(tmp.path().join("output")).await?),
+        Some((_, status_code)) => {
+            Err(format!("failed to download '{}': status code {}", cid, status_code).into())
+        }
+        None => Err(format!("failed to download '{}'", cid).into()),
+    }
+}
+
+/// Exits the process with the exit code of the finished task.
+///
+/// Declined or failed tasks without an exit code are reported as exit code 1.
+/// Returns normally if the task succeeded.
+pub fn exit_with_task_code(task: &Value) {
+    let code = process_exit_code(task);
+    if code != 0 {
+        std::process::exit(code);
+    }
+}
+
+/// Returns the process exit code for the finished task.
+///
+/// Nonzero task exit codes outside of 1..=255 are reported as 1,
+/// because the OS truncates exit codes to 8 bits.
+fn process_exit_code(task: &Value) -> i32 {
+    match task_exit_code(task) {
+        Some(0) => 0,
+        Some(code @ 1..=255) => code as i32,
+        Some(_) => 1,
+        None if task_state(task).is_some_and(|state| !state.eq_ignore_ascii_case("done")) => 1,
+        None => 0,
+    }
+}
+
+/// Creates a task, waits for it to finish and optionally downloads its outputs.
+async fn create_task_and_wait(
+    chain_args: &ChainArgs,
+    client: &mut GevulotClient,
+    task: gevulot_rs::models::Task,
+    wait_args: &WaitArgs,
+    output_dir: Option<&Path>,
+    ipfs_gateway: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    if !chain_args.signs_transactions() {
+        return Err("--wait can't be used with --generate-only or --dry-run".into());
+    }
+    let mut value = create_task(chain_args, client, task).await?;
+    let task_id = value["task_id"]
+        .as_str()
+        .ok_or("Task ID not found in response")?
+        .to_string();
+    let task = wait_task(chain_args, &task_id, wait_args).await?;
+    if let Some(output_dir) = output_dir {
+        value["outputs"] = download_task_outputs(&task, output_dir, ipfs_gateway).await?;
+    }
+    value["task"] = task;
+    Ok(value)
+}
+
+/// Downloads stored stdout/stderr and output contexts of a finished task.
+///
+/// Files are placed into `output_dir` using the same layout `local-run` produces:
+/// `stdout`, `stderr` and output contexts relative to the VM output directory.
+/// Output stored as `ipfs://` reference is downloaded through the IPFS gateway.
+async fn download_task_outputs(
+    task: &Value,
+    output_dir: &Path,
+    ipfs_gateway: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    tokio::fs::create_dir_all(output_dir).await?;
+    let status = &task["status"];
+
+    let mut stdout_file = None;
+    if let Some(stdout) = status["stdout"].as_str() {
+        let path = output_dir.join("stdout");
+        tokio::fs::write(&path, resolve_logs(stdout, ipfs_gateway).await?).await?;
+        stdout_file = Some(path);
+    }
+
+    let mut stderr_file = None;
+    if let Some(stderr) = status["stderr"].as_str() {
+        let path = output_dir.join("stderr");
+        tokio::fs::write(&path, resolve_logs(stderr, ipfs_gateway).await?).await?;
+        stderr_file = Some(path);
+    }
+
+    // Output contexts in status are stored in the same order as in task specification.
+    let sources = task["spec"]["outputContexts"]
+        .as_array()
+        .map(|contexts| {
+            contexts
+                .iter()
+                .filter_map(|context| context["source"].as_str())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let cids = status["outputContexts"]
+        .as_array()
+        .map(|cids| cids.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let mut downloads = vec![];
+    let mut output_paths = vec![];
+    for (idx, cid) in cids.iter().enumerate() {
+        let cid = cid.strip_prefix("ipfs://").unwrap_or(cid);
+        let relative = match sources.get(idx) {
+            Some(source) => mount_relative_path(source, GEVULOT_OUTPUT_MOUNTPOINT)?,
+            None if is_plain_relative(Path::new(cid)) => PathBuf::from(cid),
+            None => return Err(format!("invalid output context '{}'", cid).into()),
+        };
+        if let Some(parent) = relative.parent() {
+            tokio::fs::create_dir_all(output_dir.join(parent)).await?;
+        }
+        downloads.push(
+            Download::new(&format!("{}/{}", ipfs_gateway.trim_end_matches('/'), cid))
+                .file_name(&relative),
+        );
+        output_paths.push(output_dir.join(relative));
+    }
+
+    if !downloads.is_empty() {
+        let mut downloader = Downloader::builder().download_folder(output_dir).build()?;
+        let results = downloader.async_download(&downloads).await?;
+        for (result, cid) in results.into_iter().zip(cids.iter()) {
+            let summary = result.map_err(|err| format!("failed to download '{}': {}", cid, err))?;
+            let status_code = summary
+                .status
+                .first()
+                .ok_or(format!("failed to download '{}'", cid))?
+                .1;
+            if status_code != 200 {
+                return Err(
+                    format!("failed to download '{}': status code {}", cid, status_code).into(),
+                );
+            }
+        }
+    }
+
+    Ok(serde_json::json!({
+        "output_contexts": output_paths,
+        "stdout": stdout_file,
+        "stderr": stderr_file,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_exit_code() {
+        let task = |code: i64| serde_json::json!({"status": {"state": "Done", "exitCode": code}});
+        assert_eq!(process_exit_code(&task(0)), 0);
+        assert_eq!(process_exit_code(&task(42)), 42);
+        assert_eq!(process_exit_code(&task(256)), 1);
+        assert_eq!(process_exit_code(&task(-1)), 1);
+        assert_eq!(
+            process_exit_code(&serde_json::json!({"status": {"state": "Failed"}})),
+            1
+        );
+    }
+
+    #[test]
+    fn test_escrow() {
+        let pricing = PricingArgs {
+            cpu_price: Some(1.0),
+            gpu_price: Some(10.0),
+            memory_price: Some(0.5),
+        };
+        let resources = TaskResources {
+            cpus: 2000,
+            gpus: 500,
+            memory: 4 << 30,
+            time: 60,
+        };
+        // (2 * 1 + 0.5 * 10 + 4 * 0.5) * 60
+        assert_eq!(pricing.escrow(&resources), Ok(540));
+
+        let pricing = PricingArgs {
+            gpu_price: None,
+            ..pricing
+        };
+        assert!(pricing.escrow(&resources).is_err());
+        let resources = TaskResources {
+            gpus: 0,
+            ..resources
+        };
+        assert_eq!(pricing.escrow(&resources), Ok(240));
+    }
+}
@@ -0,0 +1,798 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use cosmrs::crypto::secp256k1::SigningKey;
+use cosmrs::proto::cosmos::bank::v1beta1::MsgSend;
+use cosmrs::proto::cosmos::base::abci::v1beta1::{TxMsgData, TxResponse};
+use cosmrs::proto::cosmos::tx::v1beta1::{
+    BroadcastMode, BroadcastTxRequest, GetTxRequest, SimulateRequest, Tx, TxRaw,
+};
+use cosmrs::tx::{Body, Fee, SignDoc, SignerInfo};
+use cosmrs::{AccountId, Any, Coin};
+use gevulot_rs::proto::gevulot::gevulot::{
+    MsgAcceptTask, MsgAckPin, MsgAnnounceWorkerExit, MsgCreatePin, MsgCreateTask, MsgCreateWorker,
+    MsgDeclineTask, MsgDeletePin, MsgDeleteTask, MsgDeleteWorker, MsgFinishTask, MsgRescheduleTask,
+    MsgUpdateWorker,
+};
+use gevulot_rs::GevulotClient;
+use patharg::InputArg;
+use prost::{Message, Name};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::keystore::Keystore;
+use crate::{connect_to_gevulot, print_object, read_file, ChainArgs, OutputArgs};
+
+/// Chain ID used when it is not configured.
+pub const DEFAULT_CHAIN_ID: &str = "gevulot";
+
+/// Denomination of transaction fees.
+pub const FEE_DENOM: &str = "ucredit";
+
+/// Gas price used when it is not configured.
+pub const DEFAULT_GAS_PRICE: f64 = 0.025;
+
+/// Gas multiplier applied to simulated gas when it is not configured.
+pub const DEFAULT_GAS_MULTIPLIER: f64 = 1.2;
+
+/// Time to wait for a broadcast transaction to be included into a block.
+const TX_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Interval between queries of a broadcast transaction.
+const TX_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tx command.
+#[derive(Clone, Debug, clap::Parser)]
+pub struct Command {
+    #[command(flatten)]
+    chain_args: ChainArgs,
+
+    #[command(subcommand)]
+    subcommand: Subcommand,
+}
+
+impl Command {
+    /// Match tx subcommand and run it.
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let chain_args = &self
+            .chain_args
+            .resolve_for(matches!(self.subcommand, Subcommand::Sign { .. }))?;
+        let value = match &self.subcommand {
+            Subcommand::Sign { file } => sign_tx(chain_args, file.path_ref().map(|v| &**v)).await,
+            Subcommand::Broadcast { file } => {
+                broadcast_tx(chain_args, file.path_ref().map(|v| &**v)).await
+            }
+            Subcommand::Get { hash } => get_tx(chain_args, hash).await,
+        }?;
+        print_object(output, &value)
+    }
+}
+
+/// Tx subcommand.
+#[derive(Clone, Debug, clap::Subcommand)]
+enum Subcommand {
+    /// Sign a transaction generated with --generate-only.
+    ///
+    /// Signing doesn't require network access. Account number and sequence are taken
+    /// from the transaction unless overridden with --account-number and --sequence.
+    Sign {
+        /// The file to read the unsigned transaction from or '-' to read from stdin.
+        #[arg(short, long, default_value_t)]
+        file: InputArg,
+    },
+
+    /// Broadcast a signed transaction.
+    Broadcast {
+        /// The file to read the signed transaction from or '-' to read from stdin.
+        #[arg(short, long, default_value_t)]
+        file: InputArg,
+    },
+
+    /// Get an included transaction with its messages decoded.
+    Get {
+        /// The hash of the transaction.
+        hash: String,
+    },
+}
+
+/// Transaction fee.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TxFee {
+    pub amount: u128,
+    pub denom: String,
+    pub gas_limit: u64,
+}
+
+/// Protobuf-encoded message of the transaction.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TxMessage {
+    pub type_url: String,
+
+    /// Base64-encoded message.
+    pub value: String,
+}
+
+/// Transaction which is ready to be signed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UnsignedTx {
+    pub chain_id: String,
+    pub signer: String,
+    pub account_number: u64,
+    pub sequence: u64,
+    #[serde(default)]
+    pub memo: String,
+    pub fee: TxFee,
+    pub messages: Vec<TxMessage>,
+}
+
+/// Signed transaction which is ready to be broadcast.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignedTx {
+    /// Hex-encoded hash of the transaction.
+    pub hash: String,
+
+    /// Base64-encoded transaction.
+    pub tx_bytes: String,
+}
+
+impl From<&Any> for TxMessage {
+    fn from(any: &Any) -> Self {
+        Self {
+            type_url: any.type_url.clone(),
+            value: BASE64.encode(&any.value),
+        }
+    }
+}
+
+impl TxMessage {
+    fn to_any(&self) -> Result<Any, Box<dyn std::error::Error>> {
+        Ok(Any {
+            type_url: self.type_url.clone(),
+            value: BASE64.decode(&self.value)?,
+        })
+    }
+}
+
+impl UnsignedTx {
+    fn body(&self) -> Result<Body, Box<dyn std::error::Error>> {
+        let messages = self
+            .messages
+            .iter()
+            .map(TxMessage::to_any)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Body::new(messages, self.memo.clone(), 0u32))
+    }
+
+    fn fee(&self) -> Result<Fee, Box<dyn std::error::Error>> {
+        let coin = Coin {
+            denom: self.fee.denom.parse().map_err(|err| err.to_string())?,
+            amount: self.fee.amount,
+        };
+        Ok(Fee::from_amount_and_gas(coin, self.fee.gas_limit))
+    }
+
+    /// Sets gas limit and fee at the gas price.
+    fn set_fee(&mut self, gas_limit: u64, gas_price: f64) {
+        self.fee.gas_limit = gas_limit;
+        self.fee.amount = (gas_limit as f64 * gas_price).ceil() as u128;
+    }
+
+    /// Signs the transaction. The key must belong to the signer of the transaction.
+    pub fn sign(&self, key: &SigningKey) -> Result<SignedTx, Box<dyn std::error::Error>> {
+        let public_key = key.public_key();
+        let signer = self
+            .signer
+            .parse::<AccountId>()
+            .map_err(|err| err.to_string())?;
+        let address = public_key
+            .account_id(signer.prefix())
+            .map_err(|err| err.to_string())?;
+        if address != signer {
+            return Err(format!(
+                "key address {} doesn't match signer {} of the transaction",
+                address, signer
+            )
+            .into());
+        }
+
+        let auth_info =
+            SignerInfo::single_direct(Some(public_key), self.sequence).auth_info(self.fee()?);
+        let chain_id = self
+            .chain_id
+            .parse::<cosmrs::tendermint::chain::Id>()
+            .map_err(|err| err.to_string())?;
+        let sign_doc = SignDoc::new(&self.body()?, &auth_info, &chain_id, self.account_number)
+            .map_err(|err| err.to_string())?;
+        let tx_bytes = sign_doc
+            .sign(key)
+            .and_then(|raw| raw.to_bytes())
+            .map_err(|err| err.to_string())?;
+        Ok(SignedTx::new(&tx_bytes))
+    }
+}
+
+impl SignedTx {
+    fn new(tx_bytes: &[u8]) -> Self {
+        Self {
+            hash: hex::encode_upper(Sha256::digest(tx_bytes)),
+            tx_bytes: BASE64.encode(tx_bytes),
+        }
+    }
+}
+
+/// Handles transaction modes in which the message must not be broadcast right away.
+///
+/// With `--generate-only` returns the unsigned transaction containing the message.
+/// With `--dry-run` returns the result of the simulation.
+/// Returns `None` if the message should be broadcast as usual.
+pub async fn intercept<M: Message + Name + fmt::Debug>(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    msg: &M,
+) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+    intercept_with_memo(chain_args, client, msg, "").await
+}
+
+/// Same as [`intercept`], but the transaction has the memo.
+pub async fn intercept_with_memo<M: Message + Name + fmt::Debug>(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    msg: &M,
+    memo: &str,
+) -> Result<Option<Value>, Box<dyn std::error::Error>> {
+    if chain_args.dry_run {
+        let decoded = vec![format!("{:#?}", msg)];
+        let value = dry_run(chain_args, client, vec![Any::from_msg(msg)?], decoded, memo).await?;
+        return Ok(Some(value));
+    }
+    if chain_args.generate_only {
+        let tx = generate(chain_args, client, vec![Any::from_msg(msg)?], memo).await?;
+        return Ok(Some(serde_json::to_value(tx)?));
+    }
+    Ok(None)
+}
+
+/// Returns the address which signs transactions.
+///
+/// With `--generate-only` or `--dry-run` the address can be passed with `--from`
+/// without having its private key locally. Address of a keystore key which is not
+/// unlocked is taken from the keystore.
+pub async fn signer_address(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let chain_args = chain_args.resolve()?;
+    if let (true, Some(from)) = (chain_args.is_locked(), &chain_args.from) {
+        let keystore = Keystore::open();
+        if keystore.contains(from).unwrap_or(false) {
+            // Stored address is derived at the default path
+            if chain_args.hd_path.path().is_some() {
+                return Err(
+                    "address of a keystore key at a custom derivation path is unknown \
+                     without unlocking it, pass the address to --from instead"
+                        .into(),
+                );
+            }
+            return Ok(keystore.get(from)?.address);
+        }
+        if from.parse::<AccountId>().is_ok() {
+            return Ok(from.clone());
+        }
+        return Err(format!("key '{}' not found", from).into());
+    }
+    Ok(client
+        .base_client
+        .write()
+        .await
+        .address
+        .clone()
+        .ok_or("No address found, did you set a mnemonic?")?)
+}
+
+/// Builds unsigned transaction with the messages.
+///
+/// Account number and sequence are queried from the chain unless overridden.
+/// Gas is simulated unless gas limit is set.
+pub async fn generate(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    messages: Vec<Any>,
+    memo: &str,
+) -> Result<UnsignedTx, Box<dyn std::error::Error>> {
+    let mut tx = build(chain_args, client, messages, memo).await?;
+    let chain_args = chain_args.with_profile()?;
+    let gas_limit = match chain_args.gas_limit {
+        Some(gas_limit) => gas_limit,
+        None => estimate_gas_limit(&chain_args, simulate(client, &tx).await?),
+    };
+    tx.set_fee(gas_limit, chain_args.gas_price.unwrap_or(DEFAULT_GAS_PRICE));
+    Ok(tx)
+}
+
+/// Simulates transaction with the messages without broadcasting it.
+///
+/// Returns used gas, gas limit and fee which would be used for the transaction,
+/// along with `decoded` representations of the messages.
+pub async fn dry_run(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    messages: Vec<Any>,
+    decoded: Vec<String>,
+    memo: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut tx = build(chain_args, client, messages, memo).await?;
+    let gas_used = simulate(client, &tx).await?;
+    let chain_args = chain_args.with_profile()?;
+    let gas_limit = chain_args
+        .gas_limit
+        .unwrap_or_else(|| estimate_gas_limit(&chain_args, gas_used));
+    if gas_limit < gas_used {
+        eprintln!(
+            "Warning: gas limit {} is lower than simulated gas usage {}",
+            gas_limit, gas_used
+        );
+    }
+    let gas_price = chain_args.gas_price.unwrap_or(DEFAULT_GAS_PRICE);
+    tx.set_fee(gas_limit, gas_price);
+
+    let messages = tx
+        .messages
+        .iter()
+        .zip(decoded)
+        .map(|(msg, decoded)| {
+            serde_json::json!({
+                "type_url": msg.type_url,
+                "message": decoded,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(serde_json::json!({
+        "status": "simulated",
+        "signer": tx.signer,
+        "gas_used": gas_used,
+        "gas_limit": gas_limit,
+        "gas_price": gas_price,
+        "fee": format!("{}{}", tx.fee.amount, tx.fee.denom),
+        "messages": messages,
+    }))
+}
+
+/// Builds transaction without fee.
+async fn build(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    messages: Vec<Any>,
+    memo: &str,
+) -> Result<UnsignedTx, Box<dyn std::error::Error>> {
+    let signer = signer_address(chain_args, client).await?;
+    let chain_args = chain_args.with_profile()?;
+    let (account_number, sequence) = match (chain_args.account_number, chain_args.sequence) {
+        (Some(account_number), Some(sequence)) => (account_number, sequence),
+        (account_number, sequence) => {
+            let account = client
+                .base_client
+                .write()
+                .await
+                .get_account(&signer)
+                .await?;
+            (
+                account_number.unwrap_or(account.account_number),
+                sequence.unwrap_or(account.sequence),
+            )
+        }
+    };
+
+    Ok(UnsignedTx {
+        chain_id: chain_args
+            .chain_id
+            .clone()
+            .unwrap_or(DEFAULT_CHAIN_ID.to_string()),
+        signer,
+        account_number,
+        sequence,
+        memo: memo.to_string(),
+        fee: TxFee {
+            amount: 0,
+            denom: FEE_DENOM.to_string(),
+            gas_limit: 0,
+        },
+        messages: messages.iter().map(TxMessage::from).collect(),
+    })
+}
+
+/// Applies gas multiplier to simulated gas.
+fn estimate_gas_limit(chain_args: &ChainArgs, gas_used: u64) -> u64 {
+    let multiplier = chain_args.gas_multiplier.unwrap_or(DEFAULT_GAS_MULTIPLIER);
+    (gas_used as f64 * multiplier).ceil() as u64
+}
+
+/// Builds unsigned transactions with at most `batch_size` messages each.
+///
+/// Sequences of the transactions are consecutive, so they must be broadcast in order.
+pub async fn generate_batches(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    messages: Vec<Any>,
+    batch_size: usize,
+) -> Result<Vec<UnsignedTx>, Box<dyn std::error::Error>> {
+    let mut chain_args = chain_args.with_profile()?;
+    let mut txs: Vec<UnsignedTx> = vec![];
+    for chunk in messages.chunks(batch_size.max(1)) {
+        // Simulation is done against the current sequence, as previous transactions are not broadcast
+        let mut tx = generate(&chain_args, client, chunk.to_vec(), "").await?;
+        chain_args.account_number = Some(tx.account_number);
+        chain_args.sequence = Some(tx.sequence);
+        tx.sequence += txs.len() as u64;
+        txs.push(tx);
+    }
+    Ok(txs)
+}
+
+/// Signs and broadcasts messages in transactions with at most `batch_size` messages each.
+///
+/// Transactions are broadcast one after another with consecutive sequences,
+/// then every transaction is awaited to be included into a block.
+/// Returns the response of every message or the error of its transaction.
+pub async fn send_batches(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    messages: Vec<Any>,
+    batch_size: usize,
+) -> Result<Vec<Result<Any, String>>, Box<dyn std::error::Error>> {
+    let mut chain_args = chain_args.resolve()?;
+    let key = chain_args.signing_key()?;
+    let gas_price = chain_args.gas_price.unwrap_or(DEFAULT_GAS_PRICE);
+
+    let mut broadcast_txs = vec![];
+    for chunk in messages.chunks(batch_size.max(1)) {
+        let mut tx = build(&chain_args, client, chunk.to_vec(), "").await?;
+        chain_args.account_number = Some(tx.account_number);
+        chain_args.sequence = Some(tx.sequence);
+
+        let result = async {
+            let gas_limit = match chain_args.gas_limit {
+                Some(gas_limit) => gas_limit,
+                None => estimate_gas_limit(&chain_args, simulate(client, &tx).await?),
+            };
+            tx.set_fee(gas_limit, gas_price);
+            let signed = tx.sign(&key)?;
+            broadcast(client, &signed).await?;
+            Ok::<_, Box<dyn std::error::Error>>(signed.hash)
+        }
+        .await;
+        if result.is_ok() {
+            // Rejected transactions don't consume the sequence
+            chain_args.sequence = Some(tx.sequence + 1);
+        }
+        broadcast_txs.push((chunk.len(), result));
+    }
+
+    let mut responses = vec![];
+    for (len, result) in broadcast_txs {
+        let result = match result {
+            Ok(hash) => wait_for_tx(client, &hash).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(msg_responses) if msg_responses.len() == len => {
+                responses.extend(msg_responses.into_iter().map(Ok))
+            }
+            Ok(_) => responses.extend(
+                std::iter::repeat(Err("unexpected number of message responses".to_string()))
+                    .take(len),
+            ),
+            Err(err) => responses.extend(std::iter::repeat(Err(err.to_string())).take(len)),
+        }
+    }
+    Ok(responses)
+}
+
+/// Signs the transaction, broadcasts it and waits for it to be included into a block.
+///
+/// Returns the hash of the transaction.
+pub async fn sign_and_send(
+    chain_args: &ChainArgs,
+    client: &GevulotClient,
+    tx: &UnsignedTx,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let signed = tx.sign(&chain_args.signing_key()?)?;
+    broadcast(client, &signed).await?;
+    wait_for_tx(client, &signed.hash).await?;
+    Ok(signed.hash)
+}
+
+/// Waits for the transaction to be included into a block and returns responses of its messages.
+pub async fn wait_for_tx(
+    client: &GevulotClient,
+    hash: &str,
+) -> Result<Vec<Any>, Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    let response = loop {
+        let request = GetTxRequest {
+            hash: hash.to_string(),
+        };
+        let result = client
+            .base_client
+            .write()
+            .await
+            .tx_client
+            .get_tx(request)
+            .await;
+        match result {
+            Ok(response) => break response.into_inner(),
+            Err(_) if started.elapsed() < TX_TIMEOUT => tokio::time::sleep(TX_POLL_INTERVAL).await,
+            Err(status) => {
+                return Err(format!(
+                    "transaction {} was not included in {}s: {}",
+                    hash,
+                    TX_TIMEOUT.as_secs(),
+                    status.message()
+                )
+                .into())
+            }
+        }
+    };
+
+    let tx = response.tx_response.ok_or("empty transaction response")?;
+    if tx.code != 0 {
+        return Err(format!(
+            "transaction {} failed with code {}: {}",
+            tx.txhash, tx.code, tx.raw_log
+        )
+        .into());
+    }
+    let data = hex::decode(&tx.data)?;
+    Ok(TxMsgData::decode(data.as_slice())?.msg_responses)
+}
+
+/// Simulates the transaction and returns used gas.
+///
+/// The transaction is not signed, the chain skips signature verification during simulation.
+pub async fn simulate(
+    client: &GevulotClient,
+    tx: &UnsignedTx,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let auth_info = SignerInfo::single_direct(None, tx.sequence).auth_info(tx.fee()?);
+    let raw = TxRaw {
+        body_bytes: tx.body()?.into_bytes().map_err(|err| err.to_string())?,
+        auth_info_bytes: auth_info.into_bytes().map_err(|err| err.to_string())?,
+        signatures: vec![vec![]],
+    };
+    #[allow(deprecated)]
+    let request = SimulateRequest {
+        tx: None,
+        tx_bytes: raw.encode_to_vec(),
+    };
+    let response = client
+        .base_client
+        .write()
+        .await
+        .tx_client
+        .simulate(request)
+        .await?
+        .into_inner();
+    Ok(response
+        .gas_info
+        .ok_or("simulation returned no gas info")?
+        .gas_used)
+}
+
+/// Broadcasts signed transaction and waits for it to be checked.
+pub async fn broadcast(
+    client: &GevulotClient,
+    tx: &SignedTx,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let request = BroadcastTxRequest {
+        tx_bytes: BASE64.decode(&tx.tx_bytes)?,
+        mode: BroadcastMode::Sync as i32,
+    };
+    let response = client
+        .base_client
+        .write()
+        .await
+        .tx_client
+        .broadcast_tx(request)
+        .await?
+        .into_inner()
+        .tx_response
+        .ok_or("empty broadcast response")?;
+    if response.code != 0 {
+        return Err(format!(
+            "transaction {} failed with code {}: {}",
+            response.txhash, response.code, response.raw_log
+        )
+        .into());
+    }
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": "Transaction broadcast successfully",
+        "hash": response.txhash,
+    }))
+}
+
+/// Retrieves the transaction by its hash.
+async fn get_tx(chain_args: &ChainArgs, hash: &str) -> Result<Value, Box<dyn std::error::Error>> {
+    let client = connect_to_gevulot(chain_args).await?;
+    let request = GetTxRequest {
+        hash: hash.to_string(),
+    };
+    let response = client
+        .base_client
+        .write()
+        .await
+        .tx_client
+        .get_tx(request)
+        .await
+        .map_err(|status| format!("transaction {}: {}", hash, status.message()))?
+        .into_inner();
+    let tx_response = response.tx_response.ok_or("empty transaction response")?;
+    Ok(tx_record(response.tx.as_ref(), &tx_response))
+}
+
+/// Describes the included transaction with its fee and decoded messages.
+pub fn tx_record(tx: Option<&Tx>, response: &TxResponse) -> Value {
+    let body = tx.and_then(|tx| tx.body.as_ref());
+    let fee = tx
+        .and_then(|tx| tx.auth_info.as_ref())
+        .and_then(|auth_info| auth_info.fee.as_ref());
+    let mut record = serde_json::json!({
+        "hash": response.txhash,
+        "height": response.height,
+        "timestamp": response.timestamp,
+        "status": if response.code == 0 { "success" } else { "failed" },
+        "code": response.code,
+        "gas_wanted": response.gas_wanted,
+        "gas_used": response.gas_used,
+        "fee": fee
+            .map(|fee| {
+                fee.amount
+                    .iter()
+                    .map(|coin| serde_json::json!({ "amount": coin.amount, "denom": coin.denom }))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default(),
+        "memo": body.map(|body| body.memo.as_str()).unwrap_or_default(),
+        "messages": body
+            .map(|body| body.messages.iter().map(decode_message).collect::<Vec<_>>())
+            .unwrap_or_default(),
+    });
+    if response.code != 0 {
+        record["raw_log"] = response.raw_log.clone().into();
+    }
+    record
+}
+
+/// Decodes the message of a known type.
+///
+/// Messages of unknown types are described only by their type URL.
+pub fn decode_message(any: &Any) -> Value {
+    let decoded = decode_as::<MsgSend>(any)
+        .or_else(|| decode_as::<MsgCreateTask>(any))
+        .or_else(|| decode_as::<MsgDeleteTask>(any))
+        .or_else(|| decode_as::<MsgRescheduleTask>(any))
+        .or_else(|| decode_as::<MsgAcceptTask>(any))
+        .or_else(|| decode_as::<MsgDeclineTask>(any))
+        .or_else(|| decode_as::<MsgFinishTask>(any))
+        .or_else(|| decode_as::<MsgCreateWorker>(any))
+        .or_else(|| decode_as::<MsgUpdateWorker>(any))
+        .or_else(|| decode_as::<MsgDeleteWorker>(any))
+        .or_else(|| decode_as::<MsgAnnounceWorkerExit>(any))
+        .or_else(|| decode_as::<MsgCreatePin>(any))
+        .or_else(|| decode_as::<MsgDeletePin>(any))
+        .or_else(|| decode_as::<MsgAckPin>(any));
+    let name = any.type_url.rsplit(['.', '/']).next().unwrap_or_default();
+    let mut message = serde_json::json!({
+        "type": name,
+        "type_url": any.type_url,
+    });
+    if let Some(decoded) = decoded {
+        message["decoded"] = decoded.into();
+    }
+    message
+}
+
+/// Decodes the message if it is of type `M`.
+fn decode_as<M: Message + Name + Default + fmt::Debug>(any: &Any) -> Option<String> {
+    if any.type_url != M::type_url() {
+        return None;
+    }
+    Some(match M::decode(any.value.as_slice()) {
+        Ok(msg) => format!("{:?}", msg),
+        Err(err) => format!("invalid message: {}", err),
+    })
+}
+
+/// Signs unsigned transaction from the file.
+async fn sign_tx(
+    chain_args: &ChainArgs,
+    path: Option<&std::path::Path>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut tx: UnsignedTx = read_file(path).await?;
+    if let Some(account_number) = chain_args.account_number {
+        tx.account_number = account_number;
+    }
+    if let Some(sequence) = chain_args.sequence {
+        tx.sequence = sequence;
+    }
+    let key = chain_args.signing_key()?;
+    Ok(serde_json::to_value(tx.sign(&key)?)?)
+}
+
+/// Broadcasts signed transaction from the file.
+async fn broadcast_tx(
+    chain_args: &ChainArgs,
+    path: Option<&std::path::Path>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let tx: SignedTx = read_file(path).await?;
+    let client = connect_to_gevulot(chain_args).await?;
+    broadcast(&client, &tx).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_message, TxFee, TxMessage, UnsignedTx};
+    use crate::keystore::KeySecret;
+
+    const PRIVATE_KEY: &str = "4f3edf983ac636a65a842ce7c78d9aa706d3b113bce9c46f30d7d21715b23b1d";
+
+    fn unsigned_tx(signer: String) -> UnsignedTx {
+        UnsignedTx {
+            chain_id: "gevulot".to_string(),
+            signer,
+            account_number: 1,
+            sequence: 2,
+            memo: String::new(),
+            fee: TxFee {
+                amount: 5000,
+                denom: "ucredit".to_string(),
+                gas_limit: 200000,
+            },
+            messages: vec![TxMessage {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".to_string(),
+                value: String::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_sign() {
+        let key = KeySecret::PrivateKey {
+            hex: PRIVATE_KEY.to_string(),
+        }
+        .signing_key()
+        .unwrap();
+        let address = key.public_key().account_id("gvlt").unwrap().to_string();
+
+        let signed = unsigned_tx(address).sign(&key).unwrap();
+        assert_eq!(signed.hash.len(), 64);
+        assert!(!signed.tx_bytes.is_empty());
+
+        let other = "gvlt1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqq7g8gmm".to_string();
+        assert!(unsigned_tx(other).sign(&key).is_err());
+    }
+
+    #[test]
+    fn test_decode_message() {
+        let msg = cosmrs::proto::cosmos::bank::v1beta1::MsgSend {
+            from_address: "gvlt1sender".to_string(),
+            to_address: "gvlt1receiver".to_string(),
+            amount: vec![],
+        };
+        let message = decode_message(&cosmrs::Any::from_msg(&msg).unwrap());
+        assert_eq!(message["type"], "MsgSend");
+        assert!(message["decoded"]
+            .as_str()
+            .unwrap()
+            .contains("gvlt1receiver"));
+
+        let unknown = cosmrs::Any {
+            type_url: "/example.v1.MsgUnknown".to_string(),
+            value: vec![],
+        };
+        let message = decode_message(&unknown);
+        assert_eq!(message["type"], "MsgUnknown");
+        assert!(message.get("decoded").is_none());
+    }
+}
@@ -0,0 +1,606 @@
+use gevulot_rs::models::{Pin, Task, TaskSpec, Worker, Workflow};
+use serde::Serialize;
+use serde_yaml::Value as YamlValue;
+use std::path::{Component, Path, PathBuf};
+
+use crate::commands::local_run::{GEVULOT_INPUT_MOUNTPOINT, GEVULOT_OUTPUT_MOUNTPOINT};
+use crate::template::TemplateArgs;
+use crate::{print_object, OutputArgs};
+
+/// Supported document kinds.
+const KINDS: [&str; 4] = ["Task", "Worker", "Pin", "Workflow"];
+
+/// Supported document versions.
+const VERSIONS: [&str; 1] = ["v0"];
+
+/// URI schemes of input contexts supported by the network.
+const INPUT_SCHEMES: [&str; 3] = ["ipfs", "http", "https"];
+
+/// Validate arguments.
+#[derive(Clone, Debug, clap::Parser)]
+pub struct ValidateArgs {
+    /// Files to validate or '-' to read from stdin.
+    #[arg(value_name = "FILE", required = true)]
+    files: Vec<PathBuf>,
+
+    #[command(flatten)]
+    template_args: TemplateArgs,
+}
+
+impl ValidateArgs {
+    /// Validate files and print found problems.
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let mut problems = vec![];
+        for file in &self.files {
+            let path = (file.as_os_str() != "-").then_some(file.as_path());
+            problems.extend(validate_input(path, &self.template_args).await?);
+        }
+        report(output, &problems)
+    }
+}
+
+/// Severity of the problem.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Problem found in the document.
+#[derive(Clone, Debug, Serialize)]
+pub struct Problem {
+    pub file: String,
+    pub line: Option<usize>,
+    pub field: Option<String>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Reads the file, directory or stdin, renders templates and validates every document in it.
+pub async fn validate_input(
+    path: Option<&Path>,
+    template_args: &TemplateArgs,
+) -> Result<Vec<Problem>, Box<dyn std::error::Error>> {
+    let problems = template_args
+        .read_documents(path)
+        .await?
+        .iter()
+        .flat_map(|doc| validate_document(&doc.file, &doc.content, doc.line))
+        .collect();
+    Ok(problems)
+}
+
+/// Prints found problems. Fails if there are any errors.
+pub fn report(output: &OutputArgs, problems: &[Problem]) -> Result<(), Box<dyn std::error::Error>> {
+    let errors = problems
+        .iter()
+        .filter(|problem| problem.severity == Severity::Error)
+        .count();
+    print_object(
+        output,
+        &serde_json::json!({
+            "valid": errors == 0,
+            "problems": problems,
+        }),
+    )?;
+    if errors > 0 {
+        return Err(format!("validation failed with {} error(s)", errors).into());
+    }
+    Ok(())
+}
+
+/// Validates the document.
+///
+/// `first_line` is the line number of the document start in the file.
+pub fn validate_document(file: &str, content: &str, first_line: usize) -> Vec<Problem> {
+    let mut checker = Checker {
+        file,
+        content,
+        first_line,
+        problems: vec![],
+    };
+    checker.check();
+    checker.problems
+}
+
+/// Validates the task specification, e.g. of a task received from the chain.
+pub fn validate_task_spec(
+    file: &str,
+    spec: &TaskSpec,
+) -> Result<Vec<Problem>, Box<dyn std::error::Error>> {
+    let value = serde_yaml::to_value(spec)?;
+    let mut checker = Checker {
+        file,
+        content: "",
+        first_line: 1,
+        problems: vec![],
+    };
+    checker.check_task_spec(spec, &value, "spec");
+    Ok(checker.problems)
+}
+
+/// Collects problems of a single document.
+struct Checker<'a> {
+    file: &'a str,
+    content: &'a str,
+    first_line: usize,
+    problems: Vec<Problem>,
+}
+
+impl Checker<'_> {
+    fn check(&mut self) {
+        let value: YamlValue = match serde_yaml::from_str(self.content) {
+            Ok(value) => value,
+            Err(err) => return self.yaml_error(err),
+        };
+
+        let kind = match value.get("kind").map(YamlValue::as_str) {
+            Some(Some(kind)) if KINDS.contains(&kind) => kind,
+            Some(Some(kind)) => {
+                return self.error(
+                    "kind",
+                    format!(
+                        "unknown kind '{}', expected one of: {}",
+                        kind,
+                        KINDS.join(", ")
+                    ),
+                )
+            }
+            Some(None) => return self.error("kind", "kind must be a string"),
+            None => return self.error("", "missing field `kind`"),
+        };
+        match value.get("version").map(YamlValue::as_str) {
+            Some(Some(version)) if VERSIONS.contains(&version) => {}
+            Some(Some(version)) => self.error(
+                "version",
+                format!(
+                    "unsupported version '{}', expected one of: {}",
+                    version,
+                    VERSIONS.join(", ")
+                ),
+            ),
+            Some(None) => self.error("version", "version must be a string"),
+            None => self.error("", "missing field `version`"),
+        }
+
+        match kind {
+            "Task" => {
+                if let Some(task) = self.parse::<Task>() {
+                    self.check_task_spec(&task.spec, &value["spec"], "spec");
+                }
+            }
+            "Worker" => {
+                if let Some(worker) = self.parse::<Worker>() {
+                    self.check_worker(&worker);
+                }
+            }
+            "Pin" => {
+                if let Some(pin) = self.parse::<Pin>() {
+                    self.check_pin(&pin, &value["spec"]);
+                }
+            }
+            "Workflow" => {
+                if let Some(workflow) = self.parse::<Workflow>() {
+                    for (i, stage) in workflow.spec.stages.iter().enumerate() {
+                        for (j, spec) in stage.tasks.iter().enumerate() {
+                            self.check_task_spec(
+                                spec,
+                                &value["spec"]["stages"][i]["tasks"][j],
+                                &format!("spec.stages[{}].tasks[{}]", i, j),
+                            );
+                        }
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Parses the document into the model, reporting missing fields and type mismatches.
+    fn parse<T: serde::de::DeserializeOwned>(&mut self) -> Option<T> {
+        match serde_yaml::from_str(self.content) {
+            Ok(parsed) => Some(parsed),
+            Err(err) => {
+                self.yaml_error(err);
+                None
+            }
+        }
+    }
+
+    fn check_task_spec(&mut self, spec: &TaskSpec, value: &YamlValue, prefix: &str) {
+        if spec.image.is_empty() {
+            self.error(&format!("{}.image", prefix), "image must not be empty");
+        } else if spec.image.starts_with("file://") {
+            self.error(
+                &format!("{}.image", prefix),
+                "local files are only supported by local-run",
+            );
+        }
+
+        let resources = format!("{}.resources", prefix);
+        match spec.resources.cpus.millicores() {
+            Ok(0) => self.error(&format!("{}.cpus", resources), "cpus must be positive"),
+            Ok(_) => {}
+            Err(err) => self.error(&format!("{}.cpus", resources), err.to_string()),
+        }
+        if let Err(err) = spec.resources.gpus.millicores() {
+            self.error(&format!("{}.gpus", resources), err.to_string());
+        }
+        match spec.resources.memory.bytes() {
+            Ok(0) => self.error(&format!("{}.memory", resources), "memory must be positive"),
+            Ok(_) => {}
+            Err(err) => self.error(&format!("{}.memory", resources), err.to_string()),
+        }
+        match spec.resources.time.seconds() {
+            Ok(0) => self.error(&format!("{}.time", resources), "time must be positive"),
+            Ok(_) => {}
+            Err(err) => self.error(&format!("{}.time", resources), err.to_string()),
+        }
+
+        for (i, env) in spec.env.iter().enumerate() {
+            if env.name.is_empty() {
+                self.error(
+                    &format!("{}.env[{}].name", prefix, i),
+                    "environment variable name must not be empty",
+                );
+            }
+        }
+
+        for (i, input) in spec.input_contexts.iter().enumerate() {
+            let field = format!("{}.inputContexts[{}]", prefix, i);
+            match input.source.split_once("://") {
+                Some((scheme, _)) if INPUT_SCHEMES.contains(&scheme) => {}
+                Some(("file", _)) => self.error(
+                    &format!("{}.source", field),
+                    "local files are only supported by local-run",
+                ),
+                Some((scheme, _)) => self.error(
+                    &format!("{}.source", field),
+                    format!(
+                        "unsupported URI scheme '{}', expected one of: {}",
+                        scheme,
+                        INPUT_SCHEMES.join(", ")
+                    ),
+                ),
+                None => self.error(
+                    &format!("{}.source", field),
+                    format!(
+                        "source must be an URI with one of the schemes: {}",
+                        INPUT_SCHEMES.join(", ")
+                    ),
+                ),
+            }
+            let target = Path::new(&input.target);
+            if !target.is_absolute() || has_parent_dir(target) {
+                self.error(
+                    &format!("{}.target", field),
+                    "target must be an absolute path without '..'",
+                );
+            } else if !target.starts_with(GEVULOT_INPUT_MOUNTPOINT) {
+                self.warning(
+                    &format!("{}.target", field),
+                    format!("target is outside of '{}'", GEVULOT_INPUT_MOUNTPOINT),
+                );
+            }
+        }
+
+        let outputs = value["outputContexts"].as_sequence().cloned();
+        for (i, output) in outputs.unwrap_or_default().iter().enumerate() {
+            let field = format!("{}.outputContexts[{}].source", prefix, i);
+            let source = Path::new(output["source"].as_str().unwrap_or_default());
+            if !source.starts_with(GEVULOT_OUTPUT_MOUNTPOINT) || has_parent_dir(source) {
+                self.error(
+                    &field,
+                    format!(
+                        "output path must be under '{}' and must not contain '..'",
+                        GEVULOT_OUTPUT_MOUNTPOINT
+                    ),
+                );
+            }
+        }
+    }
+
+    fn check_worker(&mut self, worker: &Worker) {
+        match worker.spec.cpus.millicores() {
+            Ok(0) => self.error("spec.cpus", "cpus must be positive"),
+            Ok(_) => {}
+            Err(err) => self.error("spec.cpus", err.to_string()),
+        }
+        if let Err(err) = worker.spec.gpus.millicores() {
+            self.error("spec.gpus", err.to_string());
+        }
+        match worker.spec.memory.bytes() {
+            Ok(0) => self.error("spec.memory", "memory must be positive"),
+            Ok(_) => {}
+            Err(err) => self.error("spec.memory", err.to_string()),
+        }
+        match worker.spec.disk.bytes() {
+            Ok(0) => self.error("spec.disk", "disk must be positive"),
+            Ok(_) => {}
+            Err(err) => self.error("spec.disk", err.to_string()),
+        }
+    }
+
+    fn check_pin(&mut self, pin: &Pin, value: &YamlValue) {
+        let has_fallback_urls = pin
+            .spec
+            .fallback_urls
+            .as_ref()
+            .is_some_and(|urls| !urls.is_empty());
+        if value["cid"].as_str().unwrap_or_default().is_empty() && !has_fallback_urls {
+            self.error("spec.cid", "either cid or fallbackUrls must be set");
+        }
+        for (i, url) in pin.spec.fallback_urls.iter().flatten().enumerate() {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                self.error(
+                    &format!("spec.fallbackUrls[{}]", i),
+                    "fallback URL must be an http:// or https:// URL",
+                );
+            }
+        }
+        match pin.spec.bytes.bytes() {
+            Ok(0) => self.error("spec.bytes", "bytes must be positive"),
+            Ok(_) => {}
+            Err(err) => self.error("spec.bytes", err.to_string()),
+        }
+        match pin.spec.time.seconds() {
+            Ok(0) => self.error("spec.time", "time must be positive"),
+            Ok(_) => {}
+            Err(err) => self.error("spec.time", err.to_string()),
+        }
+        if pin.spec.redundancy == 0 {
+            self.error("spec.redundancy", "redundancy must be at least 1");
+        }
+    }
+
+    fn yaml_error(&mut self, err: serde_yaml::Error) {
+        let message = err.to_string();
+        // Location is reported separately
+        let message = message
+            .split_once(" at line ")
+            .map(|(message, _)| message.to_string())
+            .unwrap_or(message);
+        self.problems.push(Problem {
+            file: self.file.to_string(),
+            line: err
+                .location()
+                .map(|location| self.first_line + location.line() - 1),
+            field: None,
+            severity: Severity::Error,
+            message,
+        });
+    }
+
+    fn error(&mut self, field: &str, message: impl Into<String>) {
+        self.push(field, Severity::Error, message.into());
+    }
+
+    fn warning(&mut self, field: &str, message: impl Into<String>) {
+        self.push(field, Severity::Warning, message.into());
+    }
+
+    fn push(&mut self, field: &str, severity: Severity, message: String) {
+        self.problems.push(Problem {
+            file: self.file.to_string(),
+            line: locate(self.content, field).map(|line| self.first_line + line - 1),
+            field: (!field.is_empty()).then(|| field.to_string()),
+            severity,
+            message,
+        });
+    }
+}
+
+fn has_parent_dir(path: &Path) -> bool {
+    path.components()
+        .any(|component| component == Component::ParentDir)
+}
+
+/// Segment of a field path.
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Splits field path like `spec.inputContexts[0].source` into segments.
+fn segments(path: &str) -> Vec<Segment<'_>> {
+    let mut segments = vec![];
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        let (key, indices) = part.split_once('[').unwrap_or((part, ""));
+        if !key.is_empty() {
+            segments.push(Segment::Key(key));
+        }
+        segments.extend(
+            indices
+                .split('[')
+                .filter_map(|index| index.trim_end_matches(']').parse().ok())
+                .map(Segment::Index),
+        );
+    }
+    segments
+}
+
+/// Finds the line of the field in YAML document by scanning its block structure.
+///
+/// Returns 1-based line number of the deepest found segment of the path.
+/// Flow style collections are not descended into.
+fn locate(content: &str, path: &str) -> Option<usize> {
+    let lines = content.lines().collect::<Vec<_>>();
+    let mut found = None;
+    let mut start = 0;
+    let mut parent_indent: isize = -1;
+    // Line of the list item, which contains the first key of the item
+    let mut item_line = None;
+
+    for segment in segments(path) {
+        let mut next = None;
+        match segment {
+            Segment::Key(key) => {
+                let mut child_indent = None;
+                for (i, line) in lines.iter().enumerate().skip(start) {
+                    let Some((indent, text)) = block_line(line) else {
+                        continue;
+                    };
+                    if Some(i) != item_line && indent <= parent_indent {
+                        break;
+                    }
+                    // Key of the list item is indented by the dash
+                    let (indent, text) = match text.strip_prefix("- ") {
+                        Some(item) => {
+                            let item = item.trim_start();
+                            (indent + (text.len() - item.len()) as isize, item)
+                        }
+                        None => (indent, text),
+                    };
+                    if *child_indent.get_or_insert(indent) != indent {
+                        continue;
+                    }
+                    let matches = text
+                        .strip_prefix(key)
+                        .map(|rest| rest.trim_start().starts_with(':'))
+                        .unwrap_or(false)
+                        || text
+                            .strip_prefix(&format!("\"{}\"", key))
+                            .or_else(|| text.strip_prefix(&format!("'{}'", key)))
+                            .is_some_and(|rest| rest.trim_start().starts_with(':'));
+                    if matches {
+                        next = Some((i, indent, i + 1, None));
+                        break;
+                    }
+                }
+            }
+            Segment::Index(index) => {
+                let mut item_indent = None;
+                let mut count = 0;
+                for (i, line) in lines.iter().enumerate().skip(start) {
+                    let Some((indent, text)) = block_line(line) else {
+                        continue;
+                    };
+                    let is_item = text == "-" || text.starts_with("- ");
+                    if indent < parent_indent || (!is_item && indent <= parent_indent) {
+                        break;
+                    }
+                    if !is_item || item_indent.is_some_and(|item_indent| item_indent != indent) {
+                        continue;
+                    }
+                    item_indent = Some(indent);
+                    if count == index {
+                        next = Some((i, indent, i, Some(i)));
+                        break;
+                    }
+                    count += 1;
+                }
+            }
+        }
+        let (line, indent, next_start, next_item_line) = next?;
+        found = Some(line + 1);
+        parent_indent = indent;
+        start = next_start;
+        item_line = next_item_line;
+    }
+    found.or(Some(1))
+}
+
+/// Returns indentation and content of the line if it is not blank or a comment.
+fn block_line(line: &str) -> Option<(isize, &str)> {
+    let text = line.trim_start();
+    if text.is_empty() || text.starts_with('#') || text == "---" {
+        return None;
+    }
+    Some(((line.len() - text.len()) as isize, text.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{locate, validate_document, validate_task_spec, Severity};
+    use gevulot_rs::models::Task;
+
+    const TASK: &str = r#"kind: Task
+version: v0
+metadata:
+  name: 'Task 1'
+  description: 'Test task'
+  tags: []
+  labels: []
+spec:
+  image: 'ipfs://QmS4ustL54uo81uU8S5PiuVWh67uB1ZWjFaBk6ST3Dor3'
+  command: ['python', 'main.py']
+  args: []
+  env: []
+  inputContexts:
+    - source: 'ipfs://QmS4ustL54uo81uU8S5PiuVWh67uB1ZWjFaBk6ST3Dor3'
+      target: '/mnt/gevulot/input/data'
+    - source: 'ftp://example.com/data'
+      target: '/mnt/gevulot/input/other'
+  outputContexts:
+    - source: '/mnt/gevulot/output/result'
+      retentionPeriod: 3600
+    - source: '/tmp/result'
+      retentionPeriod: 3600
+  resources:
+    cpus: 1000
+    gpus: 0
+    memory: 0
+    time: 3600
+"#;
+
+    #[test]
+    fn test_locate() {
+        assert_eq!(locate(TASK, "kind"), Some(1));
+        assert_eq!(locate(TASK, "spec.image"), Some(9));
+        assert_eq!(locate(TASK, "spec.inputContexts[1].source"), Some(16));
+        assert_eq!(locate(TASK, "spec.inputContexts[1].target"), Some(17));
+        assert_eq!(locate(TASK, "spec.outputContexts[0]"), Some(19));
+        assert_eq!(locate(TASK, "spec.resources.memory"), Some(26));
+        assert_eq!(locate(TASK, "spec.missing"), None);
+    }
+
+    #[test]
+    fn test_validate_task() {
+        let problems = validate_document("task.yaml", TASK, 1);
+        let errors = problems
+            .iter()
+            .filter(|problem| problem.severity == Severity::Error)
+            .map(|problem| (problem.line, problem.field.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                (Some(26), Some("spec.resources.memory")),
+                (Some(16), Some("spec.inputContexts[1].source")),
+                (Some(21), Some("spec.outputContexts[1].source")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_task_spec() {
+        let task: Task = serde_yaml::from_str(TASK).unwrap();
+        let problems = validate_task_spec("task", &task.spec).unwrap();
+        let errors = problems
+            .iter()
+            .filter(|problem| problem.severity == Severity::Error)
+            .map(|problem| (problem.line, problem.field.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                (None, Some("spec.resources.memory")),
+                (None, Some("spec.inputContexts[1].source")),
+                (None, Some("spec.outputContexts[1].source")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_kind() {
+        let problems = validate_document("file.yaml", "kind: Foo\nversion: v0\n", 1);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(1));
+        assert_eq!(problems[0].field.as_deref(), Some("kind"));
+
+        let problems = validate_document("file.yaml", "kind: Task\nversion: v1\nspec: [", 5);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].severity, Severity::Error);
+    }
+}
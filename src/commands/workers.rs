@@ -2,11 +2,26 @@ use gevulot_rs::builders::{
     ByteSize, ByteUnit, MsgAnnounceWorkerExitBuilder, MsgCreateWorkerBuilder,
     MsgDeleteWorkerBuilder, MsgUpdateWorkerBuilder,
 };
+use gevulot_rs::proto::gevulot::gevulot::{
+    MsgCreateWorker, MsgCreateWorkerResponse, MsgUpdateWorker, QueryAllWorkerRequest,
+};
 use patharg::InputArg;
 use serde_json::Value;
-use std::path::Path;
+use serde_yaml::Value as YamlValue;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::signal::unix::{signal, SignalKind};
 
-use crate::{connect_to_gevulot, print_object, read_file, ChainArgs, OutputFormat};
+use crate::commands::batch::{self, BatchArgs};
+use crate::commands::local_run::{self, is_plain_relative, VmArgs};
+use crate::commands::pins::add_to_ipfs;
+use crate::commands::tasks::{accept_task, decline_task, finish_task, task_state};
+use crate::commands::tx::{intercept, signer_address};
+use crate::commands::validate::{self, Severity};
+use crate::hardware::HostResources;
+use crate::template::TemplateArgs;
+use crate::{connect_to_gevulot, print_object, ChainArgs, ListArgs, OutputArgs};
 
 /// Workers command.
 #[derive(Clone, Debug, clap::Parser)]
@@ -20,20 +35,85 @@ pub struct Command {
 
 impl Command {
     /// Match worker subcommand and run it.
-    pub async fn run(&self, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let chain_args = &self.chain_args.resolve_for(self.subcommand.signs())?;
         let value = match &self.subcommand {
-            Subcommand::List => list_workers(&self.chain_args).await,
-            Subcommand::Get { id } => get_worker(&self.chain_args, id).await,
-            Subcommand::Create { file } => {
-                create_worker(&self.chain_args, file.path_ref().map(|v| &**v)).await
+            Subcommand::List { list_args } => list_workers(chain_args, list_args).await,
+            Subcommand::Get { id } => get_worker(chain_args, id).await,
+            Subcommand::Create {
+                file,
+                batch_args,
+                template_args,
+            } => {
+                let path = file.path_ref().map(|v| &**v);
+                let mut documents = template_args.read_documents(path).await?;
+                if documents.is_empty() {
+                    return Err("no worker documents found".into());
+                }
+                if documents.len() > 1 || path.is_some_and(Path::is_dir) {
+                    batch::create_all(
+                        chain_args,
+                        &connect_to_gevulot(chain_args).await?,
+                        documents,
+                        batch_args,
+                        create_worker_msg,
+                        |resp: MsgCreateWorkerResponse| resp.id,
+                    )
+                    .await
+                } else {
+                    let worker = serde_yaml::from_str(&documents.remove(0).content)?;
+                    create_worker(chain_args, worker).await
+                }
+            }
+            Subcommand::Delete { id } => delete_worker(chain_args, id).await,
+            Subcommand::Serve { serve_args } => serve_worker(chain_args, serve_args).await,
+            Subcommand::AnnounceExit { id } => announce_worker_exit(chain_args, id).await,
+            Subcommand::Register {
+                file,
+                name,
+                template_args,
+                detect_args,
+            } => {
+                let mut worker = match file {
+                    Some(file) => template_args.read_file(Some(file.as_path())).await?,
+                    None => new_worker_document(),
+                };
+                if let Some(name) = name {
+                    worker["metadata"]["name"] = name.clone().into();
+                }
+                detect_args.apply(&mut worker)?;
+                if detect_args.print {
+                    return print_object(output, &worker);
+                }
+                create_worker(chain_args, serde_yaml::from_value(worker)?).await
             }
-            Subcommand::Delete { id } => delete_worker(&self.chain_args, id).await,
-            Subcommand::AnnounceExit { id } => announce_worker_exit(&self.chain_args, id).await,
-            Subcommand::Update { file } => {
-                update_worker(&self.chain_args, file.path_ref().map(|v| &**v)).await
+            Subcommand::Update {
+                file,
+                id,
+                template_args,
+                detect_args,
+            } => {
+                let mut worker: YamlValue = match id {
+                    Some(id) => {
+                        let mut client = connect_to_gevulot(chain_args).await?;
+                        let worker: gevulot_rs::models::Worker =
+                            client.workers.get(id).await?.into();
+                        serde_yaml::to_value(worker)?
+                    }
+                    None => {
+                        template_args
+                            .read_file(file.path_ref().map(|v| &**v))
+                            .await?
+                    }
+                };
+                detect_args.apply(&mut worker)?;
+                if detect_args.print {
+                    return print_object(output, &worker);
+                }
+                update_worker(chain_args, serde_yaml::from_value(worker)?).await
             }
         }?;
-        print_object(format, &value)
+        print_object(output, &value)
     }
 }
 
@@ -41,7 +121,10 @@ impl Command {
 #[derive(Clone, Debug, clap::Subcommand)]
 enum Subcommand {
     /// List all workers.
-    List,
+    List {
+        #[command(flatten)]
+        list_args: ListArgs,
+    },
 
     /// Get a specific worker.
     Get {
@@ -50,10 +133,19 @@ enum Subcommand {
     },
 
     /// Create a new worker.
+    ///
+    /// Multiple workers can be created at once from a multi-document YAML file
+    /// or from a directory of worker files.
     Create {
-        /// The file to read the worker data from or '-' to read from stdin.
+        /// The file or directory to read the worker data from or '-' to read from stdin.
         #[arg(short, long, default_value_t)]
         file: InputArg,
+
+        #[command(flatten)]
+        batch_args: BatchArgs,
+
+        #[command(flatten)]
+        template_args: TemplateArgs,
     },
 
     /// Delete a worker.
@@ -68,20 +160,222 @@ enum Subcommand {
         id: String,
     },
 
+    /// Run a worker which executes assigned tasks locally.
+    ///
+    /// The worker is registered, then tasks assigned to it are accepted,
+    /// executed in QEMU like with `local-run` and finished with their results.
+    /// The worker sends periodic heartbeats and announces its exit on SIGINT or SIGTERM.
+    ///
+    /// `ipfs://` images and inputs are downloaded through the IPFS gateway,
+    /// output contexts are uploaded through the IPFS API. Tasks which can't be run are declined.
+    Serve {
+        #[command(flatten)]
+        serve_args: ServeArgs,
+    },
+
+    /// Register a new worker, optionally with resources detected on this host.
+    Register {
+        /// The file to read the worker data from.
+        ///
+        /// With `--auto` only metadata is taken from the file.
+        #[arg(short, long, required_unless_present = "auto")]
+        file: Option<PathBuf>,
+
+        /// The name of the worker. Defaults to the hostname.
+        #[arg(long)]
+        name: Option<String>,
+
+        #[command(flatten)]
+        template_args: TemplateArgs,
+
+        #[command(flatten)]
+        detect_args: DetectArgs,
+    },
+
     /// Update a worker.
     Update {
         /// The file to read the worker data from or '-' to read from stdin.
         #[arg(short, long, default_value_t)]
         file: InputArg,
+
+        /// The ID of the worker to update with detected resources.
+        ///
+        /// The current worker is taken from the chain instead of the file.
+        #[arg(long, requires = "auto")]
+        id: Option<String>,
+
+        #[command(flatten)]
+        template_args: TemplateArgs,
+
+        #[command(flatten)]
+        detect_args: DetectArgs,
     },
 }
 
+impl Subcommand {
+    /// Returns `true` if the subcommand signs transactions.
+    fn signs(&self) -> bool {
+        match self {
+            Subcommand::List { .. } | Subcommand::Get { .. } => false,
+            Subcommand::Register { detect_args, .. } | Subcommand::Update { detect_args, .. } => {
+                !detect_args.print
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Arguments controlling detection of host resources.
+#[derive(Clone, Debug, clap::Args)]
+struct DetectArgs {
+    /// Detect CPUs, memory, free disk space and VFIO-capable GPUs of this host
+    /// and use them as worker resources.
+    #[arg(long)]
+    auto: bool,
+
+    /// The path on the file system to measure free disk space on.
+    #[arg(long, value_name = "PATH", default_value = "/", requires = "auto")]
+    disk_path: PathBuf,
+
+    /// Print the worker document instead of submitting it.
+    #[arg(long)]
+    print: bool,
+}
+
+impl DetectArgs {
+    /// Sets detected resources in the worker document if `--auto` is passed.
+    fn apply(&self, worker: &mut YamlValue) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.auto {
+            return Ok(());
+        }
+        let resources = HostResources::detect(&self.disk_path)?;
+        for gpu in &resources.gpus {
+            eprintln!("Detected GPU: {}", gpu);
+        }
+        resources.apply(worker);
+        Ok(())
+    }
+}
+
+/// Returns a worker document without resources named after the host.
+fn new_worker_document() -> YamlValue {
+    let hostname = std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .map(|name| name.trim().to_string())
+        .unwrap_or_else(|_| "worker".to_string());
+    let worker = serde_json::json!({
+        "kind": "Worker",
+        "version": "v0",
+        "metadata": {
+            "name": hostname,
+            "description": "",
+            "tags": [],
+            "labels": [],
+        },
+        "spec": {},
+    });
+    serde_yaml::to_value(worker).expect("worker document is serializable")
+}
+
+/// Arguments of the worker loop.
+#[derive(Clone, Debug, clap::Args)]
+struct ServeArgs {
+    /// The file to register the worker from.
+    ///
+    /// The worker is updated if `metadata.id` is set, otherwise a new worker is created.
+    #[arg(short, long, required_unless_present = "id")]
+    file: Option<PathBuf>,
+
+    /// The ID of already registered worker to serve tasks for.
+    #[arg(long, conflicts_with = "file")]
+    id: Option<String>,
+
+    #[command(flatten)]
+    template_args: TemplateArgs,
+
+    /// Interval between task queries in seconds.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    interval: u64,
+
+    /// Interval between worker heartbeats in seconds.
+    ///
+    /// The heartbeat updates the worker with its current registration, so every heartbeat
+    /// is a transaction paying fees. It is sent between tasks, so it may be delayed by a running task.
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value_t = 60,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    heartbeat_interval: u64,
+
+    /// IPFS gateway used to download `ipfs://` images and inputs of tasks.
+    #[arg(
+        long,
+        value_name = "URL",
+        value_hint = clap::ValueHint::Url,
+        env = "GEVULOT_IPFS_GATEWAY",
+        default_value = "https://ipfs.io/ipfs"
+    )]
+    ipfs_gateway: String,
+
+    /// IPFS HTTP API to upload output contexts of tasks to, e.g. http://127.0.0.1:5001.
+    ///
+    /// Tasks with output contexts are declined if it is not set.
+    #[arg(long, value_name = "URL", env = "GEVULOT_IPFS_API")]
+    ipfs_api: Option<String>,
+
+    /// Directory to store outputs of executed tasks, one subdirectory per task.
+    #[arg(long, value_name = "DIR", default_value = "worker")]
+    work_dir: PathBuf,
+
+    #[command(flatten)]
+    vm_args: VmArgs,
+
+    /// PCI device path to GPU device passed to VMs.
+    #[arg(long)]
+    gpu: Vec<String>,
+}
+
 /// Lists all workers.
-async fn list_workers(chain_args: &ChainArgs) -> Result<Value, Box<dyn std::error::Error>> {
+async fn list_workers(
+    chain_args: &ChainArgs,
+    list_args: &ListArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let workers = client.workers.list().await?;
-    let workers: Vec<gevulot_rs::models::Worker> = workers.into_iter().map(Into::into).collect();
-    Ok(serde_json::json!(workers))
+    let me = if list_args.mine {
+        Some(signer_address(chain_args, &client).await?)
+    } else {
+        None
+    };
+    let (workers, list_args) = match list_args.page_request() {
+        Some(pagination) => {
+            let request = QueryAllWorkerRequest {
+                pagination: Some(pagination),
+            };
+            let workers = client
+                .base_client
+                .write()
+                .await
+                .gevulot_client
+                .worker_all(request)
+                .await?
+                .into_inner()
+                .worker;
+            (workers, list_args.without_pagination())
+        }
+        None => (client.workers.list().await?, list_args.clone()),
+    };
+    let workers = workers
+        .into_iter()
+        .map(|worker| serde_json::json!(gevulot_rs::models::Worker::from(worker)))
+        .collect();
+    Ok(serde_json::json!(list_args.apply(workers, me.as_deref())))
 }
 
 /// Retrieves a specific worker by ID.
@@ -98,33 +392,15 @@ async fn get_worker(
 /// Creates a new worker based on the provided configuration.
 async fn create_worker(
     chain_args: &ChainArgs,
-    path: Option<&Path>,
+    worker: gevulot_rs::models::Worker,
 ) -> Result<Value, Box<dyn std::error::Error>> {
-    let worker: gevulot_rs::models::Worker = read_file(path).await?;
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
-    let resp = client
-        .workers
-        .create(
-            MsgCreateWorkerBuilder::default()
-                .creator(me)
-                .name(worker.metadata.name)
-                .description(worker.metadata.description)
-                .tags(worker.metadata.tags.into_iter().collect())
-                .labels(worker.metadata.labels.into_iter().map(Into::into).collect())
-                .cpus(worker.spec.cpus.millicores()?)
-                .gpus(worker.spec.gpus.millicores()?)
-                .memory(ByteSize::new(worker.spec.memory.bytes()?, ByteUnit::Byte))
-                .disk(ByteSize::new(worker.spec.disk.bytes()?, ByteUnit::Byte))
-                .into_message()?,
-        )
-        .await?;
+    let me = signer_address(chain_args, &client).await?;
+    let msg = create_worker_msg(worker, me)?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    let resp = client.workers.create(msg).await?;
 
     Ok(serde_json::json!({
         "status": "success",
@@ -133,44 +409,38 @@ async fn create_worker(
     }))
 }
 
+/// Builds the message creating the worker.
+pub fn create_worker_msg(
+    worker: gevulot_rs::models::Worker,
+    creator: String,
+) -> Result<MsgCreateWorker, Box<dyn std::error::Error>> {
+    let msg = MsgCreateWorkerBuilder::default()
+        .creator(creator)
+        .name(worker.metadata.name)
+        .description(worker.metadata.description)
+        .tags(worker.metadata.tags.into_iter().collect())
+        .labels(worker.metadata.labels.into_iter().map(Into::into).collect())
+        .cpus(worker.spec.cpus.millicores()?)
+        .gpus(worker.spec.gpus.millicores()?)
+        .memory(ByteSize::new(worker.spec.memory.bytes()?, ByteUnit::Byte))
+        .disk(ByteSize::new(worker.spec.disk.bytes()?, ByteUnit::Byte))
+        .into_message()?;
+    Ok(msg)
+}
+
 /// Updates a worker with the specified ID.
 async fn update_worker(
     chain_args: &ChainArgs,
-    path: Option<&Path>,
+    worker: gevulot_rs::models::Worker,
 ) -> Result<Value, Box<dyn std::error::Error>> {
-    let worker: gevulot_rs::models::Worker = read_file(path).await?;
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
-    let id = worker.metadata.id.ok_or("Worker ID not found")?;
-    client
-        .workers
-        .update(
-            MsgUpdateWorkerBuilder::default()
-                .creator(me)
-                .id(id.clone())
-                .name(worker.metadata.name)
-                .description(worker.metadata.description)
-                .tags(worker.metadata.tags.into_iter().collect())
-                .labels(worker.metadata.labels.into_iter().map(Into::into).collect())
-                .cpus(worker.spec.cpus.millicores()? as u64)
-                .gpus(worker.spec.gpus.millicores()? as u64)
-                .memory(ByteSize::new(
-                    worker.spec.memory.bytes()? as u64,
-                    ByteUnit::Byte,
-                ))
-                .disk(ByteSize::new(
-                    worker.spec.disk.bytes()? as u64,
-                    ByteUnit::Byte,
-                ))
-                .into_message()?,
-        )
-        .await?;
+    let me = signer_address(chain_args, &client).await?;
+    let msg = update_worker_msg(worker, me)?;
+    let id = msg.id.clone();
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.workers.update(msg).await?;
 
     Ok(serde_json::json!({
         "status": "success",
@@ -179,29 +449,49 @@ async fn update_worker(
     }))
 }
 
+/// Builds the message updating the worker. The worker must have its ID set.
+pub fn update_worker_msg(
+    worker: gevulot_rs::models::Worker,
+    creator: String,
+) -> Result<MsgUpdateWorker, Box<dyn std::error::Error>> {
+    let id = worker.metadata.id.ok_or("Worker ID not found")?;
+    let msg = MsgUpdateWorkerBuilder::default()
+        .creator(creator)
+        .id(id)
+        .name(worker.metadata.name)
+        .description(worker.metadata.description)
+        .tags(worker.metadata.tags.into_iter().collect())
+        .labels(worker.metadata.labels.into_iter().map(Into::into).collect())
+        .cpus(worker.spec.cpus.millicores()? as u64)
+        .gpus(worker.spec.gpus.millicores()? as u64)
+        .memory(ByteSize::new(
+            worker.spec.memory.bytes()? as u64,
+            ByteUnit::Byte,
+        ))
+        .disk(ByteSize::new(
+            worker.spec.disk.bytes()? as u64,
+            ByteUnit::Byte,
+        ))
+        .into_message()?;
+    Ok(msg)
+}
+
 /// Deletes a worker with the specified ID.
 async fn delete_worker(
     chain_args: &ChainArgs,
     worker_id: &str,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
+    let me = signer_address(chain_args, &client).await?;
 
-    client
-        .workers
-        .delete(
-            MsgDeleteWorkerBuilder::default()
-                .creator(me.clone())
-                .id(worker_id.to_string())
-                .into_message()?,
-        )
-        .await?;
+    let msg = MsgDeleteWorkerBuilder::default()
+        .creator(me.clone())
+        .id(worker_id.to_string())
+        .into_message()?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.workers.delete(msg).await?;
 
     Ok(serde_json::json!({
         "status": "success",
@@ -214,24 +504,355 @@ async fn announce_worker_exit(
     worker_id: &str,
 ) -> Result<Value, Box<dyn std::error::Error>> {
     let mut client = connect_to_gevulot(chain_args).await?;
-    let me = client
-        .base_client
-        .write()
-        .await
-        .address
-        .clone()
-        .ok_or("No address found, did you set a mnemonic?")?;
-    client
-        .workers
-        .announce_exit(
-            MsgAnnounceWorkerExitBuilder::default()
-                .creator(me)
-                .worker_id(worker_id.to_string())
-                .into_message()?,
-        )
-        .await?;
+    let me = signer_address(chain_args, &client).await?;
+    let msg = MsgAnnounceWorkerExitBuilder::default()
+        .creator(me)
+        .worker_id(worker_id.to_string())
+        .into_message()?;
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.workers.announce_exit(msg).await?;
     Ok(serde_json::json!({
         "status": "success",
         "message": format!("Worker {} announced exit successfully", worker_id)
     }))
 }
+
+/// Registers the worker and executes tasks assigned to it until interrupted.
+async fn serve_worker(
+    chain_args: &ChainArgs,
+    serve_args: &ServeArgs,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let chain_args = chain_args.unlock()?;
+    if !chain_args.signs_transactions() {
+        return Err("worker serve can't be used with --generate-only or --dry-run".into());
+    }
+
+    let worker_id = match (&serve_args.id, &serve_args.file) {
+        (Some(id), _) => id.clone(),
+        (None, Some(file)) => {
+            let worker: gevulot_rs::models::Worker = serve_args
+                .template_args
+                .read_file(Some(file.as_path()))
+                .await?;
+            let value = if worker.metadata.id.is_some() {
+                update_worker(&chain_args, worker).await?
+            } else {
+                create_worker(&chain_args, worker).await?
+            };
+            value["worker_id"]
+                .as_str()
+                .ok_or("Worker ID not found in response")?
+                .to_string()
+        }
+        (None, None) => return Err("either --file or --id is required".into()),
+    };
+    eprintln!("Worker {}: serving tasks", worker_id);
+
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut handled = HashSet::new();
+    let (mut succeeded, mut failed) = (0, 0);
+    let mut idle = false;
+    let mut last_heartbeat: Option<Instant> = None;
+    loop {
+        let delay = Duration::from_secs(if idle { serve_args.interval } else { 0 });
+        tokio::select! {
+            biased;
+            _ = interrupt.recv() => break,
+            _ = terminate.recv() => break,
+            _ = tokio::time::sleep(delay) => {}
+        }
+
+        let heartbeat_interval = Duration::from_secs(serve_args.heartbeat_interval);
+        if last_heartbeat.is_none_or(|instant| instant.elapsed() >= heartbeat_interval) {
+            if let Err(err) = send_heartbeat(&chain_args, &worker_id).await {
+                eprintln!("Worker {}: failed to send heartbeat: {}", worker_id, err);
+            }
+            last_heartbeat = Some(Instant::now());
+        }
+
+        let task = match assigned_tasks(&chain_args, &worker_id).await {
+            Ok(tasks) => tasks.into_iter().find(|task| {
+                task.metadata
+                    .id
+                    .as_ref()
+                    .is_some_and(|id| !handled.contains(id))
+            }),
+            Err(err) => {
+                eprintln!("Worker {}: failed to query tasks: {}", worker_id, err);
+                None
+            }
+        };
+        idle = task.is_none();
+        let Some(task) = task else {
+            continue;
+        };
+
+        let task_id = task.metadata.id.clone().unwrap_or_default();
+        handled.insert(task_id.clone());
+        match execute_task(&chain_args, &worker_id, task, serve_args).await {
+            Ok(true) => succeeded += 1,
+            Ok(false) => failed += 1,
+            Err(err) => {
+                failed += 1;
+                eprintln!("Task {}: {}", task_id, err);
+            }
+        }
+    }
+
+    eprintln!("Worker {}: shutting down", worker_id);
+    announce_worker_exit(&chain_args, &worker_id).await?;
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("Worker {} announced exit successfully", worker_id),
+        "worker_id": worker_id,
+        "tasks_succeeded": succeeded,
+        "tasks_failed": failed,
+    }))
+}
+
+/// Sends a heartbeat of the worker by updating it with its current registration.
+async fn send_heartbeat(
+    chain_args: &ChainArgs,
+    worker_id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = connect_to_gevulot(chain_args).await?;
+    let me = signer_address(chain_args, &client).await?;
+    let worker: gevulot_rs::models::Worker = client.workers.get(worker_id).await?.into();
+    client
+        .workers
+        .update(update_worker_msg(worker, me)?)
+        .await?;
+    Ok(())
+}
+
+/// Returns pending tasks assigned to the worker.
+async fn assigned_tasks(
+    chain_args: &ChainArgs,
+    worker_id: &str,
+) -> Result<Vec<gevulot_rs::models::Task>, Box<dyn std::error::Error>> {
+    let mut client = connect_to_gevulot(chain_args).await?;
+    let tasks = client
+        .tasks
+        .list()
+        .await?
+        .into_iter()
+        .map(gevulot_rs::models::Task::from)
+        .filter(|task| is_assigned(&serde_json::json!(task), worker_id))
+        .collect();
+    Ok(tasks)
+}
+
+/// Returns `true` if the task from its JSON representation is pending and assigned to the worker.
+fn is_assigned(task: &Value, worker_id: &str) -> bool {
+    task_state(task).is_some_and(|state| state.eq_ignore_ascii_case("pending"))
+        && task["status"]["assignedWorkers"]
+            .as_array()
+            .is_some_and(|workers| workers.iter().any(|id| id.as_str() == Some(worker_id)))
+}
+
+/// Accepts the task, runs it in local VM and finishes it with the results.
+///
+/// Tasks which can't be run by this worker are declined.
+/// Returns `true` if the task succeeded.
+async fn execute_task(
+    chain_args: &ChainArgs,
+    worker_id: &str,
+    task: gevulot_rs::models::Task,
+    serve_args: &ServeArgs,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let task_id = task.metadata.id.clone().ok_or("Task ID not found")?;
+    let mut spec = task.spec;
+    let mut problems = validate::validate_task_spec(&task_id, &spec)?
+        .into_iter()
+        .filter(|problem| problem.severity == Severity::Error)
+        .map(|problem| format!("{}: {}", problem.field.unwrap_or_default(), problem.message))
+        .collect::<Vec<_>>();
+    if !is_plain_relative(Path::new(&task_id)) {
+        problems.push(format!(
+            "task ID '{}' is not a valid directory name",
+            task_id
+        ));
+    }
+    if !spec.output_contexts.is_empty() && serve_args.ipfs_api.is_none() {
+        problems.push("output contexts can't be uploaded without --ipfs-api".to_string());
+    }
+    if !problems.is_empty() {
+        decline_task(chain_args, &task_id, worker_id).await?;
+        return Err(format!("declined: {}", problems.join("; ")).into());
+    }
+    accept_task(chain_args, &task_id, worker_id).await?;
+    eprintln!("Task {}: accepted, running", task_id);
+
+    resolve_ipfs_sources(&mut spec, &serve_args.ipfs_gateway);
+    let output_dir = std::path::absolute(serve_args.work_dir.join(&task_id))?;
+    let execution = local_run::execute(
+        &serve_args.vm_args,
+        &serve_args.gpu,
+        spec,
+        &output_dir,
+        false,
+        false,
+    )
+    .await;
+    let (mut error, exit_code, stdout, stderr, output_paths) = match execution {
+        Ok(execution) => {
+            let read = |path: Option<PathBuf>| async move {
+                match path {
+                    Some(path) => tokio::fs::read_to_string(path).await.ok(),
+                    None => None,
+                }
+            };
+            (
+                execution.error,
+                execution.exit_code,
+                read(execution.stdout).await,
+                read(execution.stderr).await,
+                execution.output_contexts,
+            )
+        }
+        Err(err) => (Some(format!("{:#}", err)), None, None, None, vec![]),
+    };
+
+    // Output contexts are reported in the order of the task specification
+    let mut output_contexts = vec![];
+    if let Some(ipfs_api) = &serve_args.ipfs_api {
+        for path in &output_paths {
+            match add_to_ipfs(ipfs_api, path, true).await {
+                Ok(cid) => output_contexts.push(format!("ipfs://{}", cid)),
+                Err(err) => {
+                    error = Some(format!(
+                        "failed to upload output {}: {}",
+                        path.display(),
+                        err
+                    ));
+                    output_contexts.clear();
+                    break;
+                }
+            }
+        }
+    }
+
+    // -1 is reported if the VM didn't exit on its own.
+    finish_task(
+        chain_args,
+        &task_id,
+        exit_code.unwrap_or(-1),
+        stdout.as_ref(),
+        stderr.as_ref(),
+        error.as_ref(),
+        Some(&output_contexts),
+    )
+    .await?;
+    match &error {
+        Some(error) => eprintln!("Task {}: failed: {}", task_id, error),
+        None => eprintln!("Task {}: done", task_id),
+    }
+    Ok(error.is_none())
+}
+
+/// Rewrites `ipfs://` image and input sources of the task into URLs of the IPFS gateway.
+fn resolve_ipfs_sources(spec: &mut gevulot_rs::models::TaskSpec, ipfs_gateway: &str) {
+    let resolve = |uri: &mut String| {
+        if let Some(cid) = uri.strip_prefix("ipfs://") {
+            *uri = format!("{}/{}", ipfs_gateway.trim_end_matches('/'), cid);
+        }
+    };
+    resolve(&mut spec.image);
+    for input in &mut spec.input_contexts {
+        resolve(&mut input.source);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_assigned, resolve_ipfs_sources, update_worker_msg};
+    use gevulot_rs::proto::gevulot::gevulot::{Metadata, Worker, WorkerSpec};
+
+    #[test]
+    fn test_is_assigned() {
+        let task = serde_json::json!({
+            "status": {
+                "state": "Pending",
+                "assignedWorkers": ["w1", "w2"],
+            },
+        });
+        assert!(is_assigned(&task, "w2"));
+        assert!(!is_assigned(&task, "w3"));
+
+        let task = serde_json::json!({
+            "status": {
+                "state": "Running",
+                "assignedWorkers": ["w1"],
+            },
+        });
+        assert!(!is_assigned(&task, "w1"));
+    }
+
+    #[test]
+    fn test_heartbeat_keeps_resources() {
+        // Heartbeat updates the worker queried from the chain with its own registration
+        let worker = Worker {
+            metadata: Some(Metadata {
+                id: "w1".to_string(),
+                name: "worker".to_string(),
+                ..Default::default()
+            }),
+            spec: Some(WorkerSpec {
+                cpus: 8000,
+                gpus: 1500,
+                memory: 16 << 30,
+                disk: 100 << 30,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let msg = update_worker_msg(worker.into(), "creator".to_string()).unwrap();
+        assert_eq!(msg.id, "w1");
+        assert_eq!(msg.cpus, 8000);
+        assert_eq!(msg.gpus, 1500);
+        assert_eq!(msg.memory, 16 << 30);
+        assert_eq!(msg.disk, 100 << 30);
+    }
+
+    #[test]
+    fn test_resolve_ipfs_sources() {
+        let task: gevulot_rs::models::Task = serde_yaml::from_str(
+            r#"kind: Task
+version: v0
+metadata:
+  name: 'Task 1'
+  description: ''
+  tags: []
+  labels: []
+spec:
+  image: 'ipfs://bafyimage'
+  command: ['main']
+  args: []
+  env: []
+  outputContexts: []
+  inputContexts:
+    - source: 'ipfs://bafyinput'
+      target: '/mnt/gevulot/input/data'
+    - source: 'https://example.com/data'
+      target: '/mnt/gevulot/input/other'
+  resources:
+    cpus: 1000
+    gpus: 0
+    memory: 1024
+    time: 3600
+"#,
+        )
+        .unwrap();
+        let mut spec = task.spec;
+        resolve_ipfs_sources(&mut spec, "https://ipfs.io/ipfs/");
+        assert_eq!(spec.image, "https://ipfs.io/ipfs/bafyimage");
+        assert_eq!(
+            spec.input_contexts[0].source,
+            "https://ipfs.io/ipfs/bafyinput"
+        );
+        assert_eq!(spec.input_contexts[1].source, "https://example.com/data");
+    }
+}
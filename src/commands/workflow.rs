@@ -1,7 +1,10 @@
+use gevulot_rs::proto::gevulot::gevulot as proto;
 use patharg::InputArg;
+use serde_json::Value;
 use std::path::Path;
 
-use crate::ChainArgs;
+use crate::commands::tx::{intercept, signer_address};
+use crate::{connect_to_gevulot, print_object, read_file, ChainArgs, OutputArgs};
 
 /// Workflow command.
 #[derive(Clone, Debug, clap::Parser)]
@@ -12,15 +15,18 @@ pub struct Command {
 
 impl Command {
     /// Match workflow subcommand and run it.
-    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
-        match &self.subcommand {
+    pub async fn run(&self, output: &OutputArgs) -> Result<(), Box<dyn std::error::Error>> {
+        let value = match &self.subcommand {
             Subcommand::List { chain_args } => list_workflows(chain_args).await,
             Subcommand::Get { chain_args, id } => get_workflow(chain_args, id).await,
             Subcommand::Create { chain_args, file } => {
-                create_workflow(chain_args, file.path_ref().map(|v| &**v)).await
+                create_workflow(&chain_args.unlock()?, file.path_ref().map(|v| &**v)).await
             }
-            Subcommand::Delete { chain_args, id } => delete_workflow(chain_args, id).await,
-        }
+            Subcommand::Delete { chain_args, id } => {
+                delete_workflow(&chain_args.unlock()?, id).await
+            }
+        }?;
+        print_object(output, &value)
     }
 }
 
@@ -66,27 +72,130 @@ enum Subcommand {
     },
 }
 
-async fn list_workflows(_chain_args: &ChainArgs) -> Result<(), Box<dyn std::error::Error>> {
-    todo!("Listing all workflows");
+/// Lists all workflows.
+async fn list_workflows(chain_args: &ChainArgs) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut client = connect_to_gevulot(chain_args).await?;
+    let workflows = client.workflows.list().await?;
+    let workflows: Vec<gevulot_rs::models::Workflow> =
+        workflows.into_iter().map(Into::into).collect();
+    Ok(serde_json::json!(workflows))
 }
 
+/// Retrieves a specific workflow by ID.
 async fn get_workflow(
-    _chain_args: &ChainArgs,
-    _workflow_id: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    todo!("Getting a specific workflow");
+    chain_args: &ChainArgs,
+    workflow_id: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut client = connect_to_gevulot(chain_args).await?;
+    let workflow = client.workflows.get(workflow_id).await?;
+    let workflow: gevulot_rs::models::Workflow = workflow.into();
+    Ok(serde_json::json!(workflow))
 }
 
+/// Creates a new workflow based on the provided specification.
+///
+/// Every stage of the workflow is converted into its on-chain representation
+/// with the same unit conversions `task create` applies to a single task.
 async fn create_workflow(
-    _chain_args: &ChainArgs,
-    _path: Option<&Path>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    todo!("Creating a new workflow");
+    chain_args: &ChainArgs,
+    path: Option<&Path>,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let workflow: gevulot_rs::models::Workflow = read_file(path).await?;
+    let mut client = connect_to_gevulot(chain_args).await?;
+    let me = signer_address(chain_args, &client).await?;
+
+    let stages = workflow
+        .spec
+        .stages
+        .into_iter()
+        .map(|stage| {
+            let tasks = stage
+                .tasks
+                .into_iter()
+                .map(task_spec_to_proto)
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(proto::WorkflowStage { tasks })
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+    let msg = proto::MsgCreateWorkflow {
+        creator: me,
+        spec: Some(proto::WorkflowSpec { stages }),
+    };
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    let resp = client.workflows.create(msg).await?;
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": "Workflow created successfully",
+        "workflow_id": resp.id
+    }))
 }
 
+/// Deletes a workflow with the specified ID.
 async fn delete_workflow(
-    _chain_args: &ChainArgs,
-    _workflow_id: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    todo!("Deleting a workflow");
+    chain_args: &ChainArgs,
+    workflow_id: &str,
+) -> Result<Value, Box<dyn std::error::Error>> {
+    let mut client = connect_to_gevulot(chain_args).await?;
+    let me = signer_address(chain_args, &client).await?;
+
+    let msg = proto::MsgDeleteWorkflow {
+        creator: me,
+        id: workflow_id.to_string(),
+    };
+    if let Some(value) = intercept(chain_args, &client, &msg).await? {
+        return Ok(value);
+    }
+    client.workflows.delete(msg).await?;
+
+    Ok(serde_json::json!({
+        "status": "success",
+        "message": format!("Workflow {} deleted successfully", workflow_id)
+    }))
+}
+
+/// Converts task specification of a workflow stage into its protobuf form.
+fn task_spec_to_proto(
+    spec: gevulot_rs::models::TaskSpec,
+) -> Result<proto::TaskSpec, Box<dyn std::error::Error>> {
+    Ok(proto::TaskSpec {
+        image: spec.image,
+        command: spec.command,
+        args: spec.args,
+        env: spec
+            .env
+            .into_iter()
+            .map(|e| proto::TaskEnv {
+                name: e.name,
+                value: e.value,
+            })
+            .collect(),
+        input_contexts: spec
+            .input_contexts
+            .into_iter()
+            .map(|ic| proto::InputContext {
+                source: ic.source,
+                target: ic.target,
+            })
+            .collect(),
+        output_contexts: spec
+            .output_contexts
+            .into_iter()
+            .map(|oc| proto::OutputContext {
+                source: oc.source,
+                retention_period: oc.retention_period as u64,
+            })
+            .collect(),
+        resources: Some(proto::TaskResources {
+            cpus: spec.resources.cpus.millicores()? as u64,
+            gpus: spec.resources.gpus.millicores()? as u64,
+            memory: spec.resources.memory.bytes()? as u64,
+            time: spec.resources.time.seconds()? as u64,
+        }),
+        store_stdout: spec.store_stdout,
+        store_stderr: spec.store_stderr,
+    })
 }
@@ -0,0 +1,217 @@
+//! Configuration file with named profiles of chain connection settings.
+
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+
+/// Environment variable overriding configuration file path.
+pub const CONFIG_PATH_ENV: &str = "GEVULOT_CONFIG";
+
+/// Name of the profile used when no profile is selected.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Gevulot Control configuration.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    /// Profile used when no profile is passed explicitly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_profile: Option<String>,
+
+    /// Named profiles.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Named set of chain connection settings.
+///
+/// Every field corresponds to an option of `ChainArgs`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_multiplier: Option<f64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+impl Config {
+    /// Returns path to the configuration file.
+    ///
+    /// Normally it will be `$HOME/.config/gvltctl/config.toml` on Linux
+    /// or `$HOME/Library/Application Support/gevulot.gvltctl/config.toml` on MacOS.
+    /// Can be overridden with `GEVULOT_CONFIG` environment variable.
+    pub fn path() -> PathBuf {
+        if let Some(path) = std::env::var_os(CONFIG_PATH_ENV) {
+            return PathBuf::from(path);
+        }
+        ProjectDirs::from("", "gevulot", "gvltctl")
+            .map(|dirs| dirs.config_dir().to_path_buf())
+            .unwrap_or(PathBuf::from(".config"))
+            .join("config.toml")
+    }
+
+    /// Loads configuration from the file. Returns empty configuration if the file doesn't exist.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let path = Self::path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        toml::from_str(&content)
+            .map_err(|err| format!("failed to parse config {}: {}", path.display(), err).into())
+    }
+
+    /// Saves configuration into the file.
+    ///
+    /// The file is only readable by the owner, because profiles may contain secrets.
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string(self)?;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&path)?;
+        file.write_all(content.as_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the name of the profile to use.
+    ///
+    /// Explicitly passed name takes precedence over the current profile.
+    pub fn profile_name<'a>(&'a self, name: Option<&'a str>) -> &'a str {
+        name.or(self.current_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Returns the profile to take chain settings from.
+    ///
+    /// Explicitly requested profile must exist.
+    pub fn profile(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Option<&Profile>, Box<dyn std::error::Error>> {
+        let profile_name = self.profile_name(name);
+        match self.profiles.get(profile_name) {
+            Some(profile) => Ok(Some(profile)),
+            None if name.is_some() => Err(format!(
+                "profile '{}' not found in {}",
+                profile_name,
+                Self::path().display()
+            )
+            .into()),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Profile {
+    /// Names of the profile settings.
+    pub const KEYS: [&'static str; 9] = [
+        "endpoint",
+        "chain-id",
+        "gas-price",
+        "gas-multiplier",
+        "gas-limit",
+        "mnemonic",
+        "private-key",
+        "password",
+        "from",
+    ];
+
+    /// Settings which are hidden when profiles are listed.
+    const SECRET_KEYS: [&'static str; 3] = ["mnemonic", "private-key", "password"];
+
+    /// Returns the value of the setting.
+    pub fn get(&self, key: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        Ok(match key {
+            "endpoint" => self.endpoint.clone(),
+            "chain-id" => self.chain_id.clone(),
+            "gas-price" => self.gas_price.map(|v| v.to_string()),
+            "gas-multiplier" => self.gas_multiplier.map(|v| v.to_string()),
+            "gas-limit" => self.gas_limit.map(|v| v.to_string()),
+            "mnemonic" => self.mnemonic.clone(),
+            "private-key" => self.private_key.clone(),
+            "password" => self.password.clone(),
+            "from" => self.from.clone(),
+            _ => return Err(unknown_key(key)),
+        })
+    }
+
+    /// Sets the value of the setting. `None` removes the setting.
+    pub fn set(
+        &mut self,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let value = value.map(str::to_string);
+        match key {
+            "endpoint" => self.endpoint = value,
+            "chain-id" => self.chain_id = value,
+            "gas-price" => self.gas_price = value.map(|v| v.parse()).transpose()?,
+            "gas-multiplier" => self.gas_multiplier = value.map(|v| v.parse()).transpose()?,
+            "gas-limit" => self.gas_limit = value.map(|v| v.parse()).transpose()?,
+            "mnemonic" => self.mnemonic = value,
+            "private-key" => self.private_key = value,
+            "password" => self.password = value,
+            "from" => self.from = value,
+            _ => return Err(unknown_key(key)),
+        }
+        Ok(())
+    }
+
+    /// Returns the profile as a map of settings with secrets hidden.
+    pub fn masked(&self) -> BTreeMap<&'static str, String> {
+        Self::KEYS
+            .iter()
+            .filter_map(|key| {
+                let value = self.get(key).ok().flatten()?;
+                if Self::SECRET_KEYS.contains(key) {
+                    Some((*key, "<hidden>".to_string()))
+                } else {
+                    Some((*key, value))
+                }
+            })
+            .collect()
+    }
+}
+
+fn unknown_key(key: &str) -> Box<dyn std::error::Error> {
+    format!(
+        "unknown setting '{}', expected one of: {}",
+        key,
+        Profile::KEYS.join(", ")
+    )
+    .into()
+}
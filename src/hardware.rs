@@ -0,0 +1,106 @@
+//! Detection of host resources available to a worker.
+
+use serde::Serialize;
+use serde_yaml::Value as YamlValue;
+use std::path::Path;
+
+/// Directory with PCI devices in sysfs.
+const SYSFS_PCI_DEVICES: &str = "/sys/bus/pci/devices";
+
+/// Resources of the host.
+#[derive(Clone, Debug, Serialize)]
+pub struct HostResources {
+    /// Number of CPU cores.
+    pub cpus: u64,
+
+    /// Total memory in bytes.
+    pub memory: u64,
+
+    /// Free disk space in bytes.
+    pub disk: u64,
+
+    /// PCI addresses of GPUs which can be passed to VMs with VFIO.
+    pub gpus: Vec<String>,
+}
+
+impl HostResources {
+    /// Detects resources of the host. Free disk space is measured on the file system of `disk_path`.
+    pub fn detect(disk_path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let meminfo = std::fs::read_to_string("/proc/meminfo")?;
+        let memory = parse_meminfo(&meminfo).ok_or("failed to read total memory")?;
+        let stat = nix::sys::statvfs::statvfs(disk_path)
+            .map_err(|err| format!("{}: {}", disk_path.display(), err))?;
+        // Types of statvfs fields differ between platforms
+        #[allow(clippy::unnecessary_cast)]
+        let disk = stat.blocks_available() as u64 * stat.fragment_size() as u64;
+        Ok(Self {
+            cpus: num_cpus::get() as u64,
+            memory,
+            disk,
+            gpus: vfio_gpus(Path::new(SYSFS_PCI_DEVICES)),
+        })
+    }
+
+    /// Sets resources in the specification of the worker document.
+    ///
+    /// CPUs and GPUs are set in millicores, memory and disk in bytes.
+    pub fn apply(&self, worker: &mut YamlValue) {
+        let spec = &mut worker["spec"];
+        spec["cpus"] = (self.cpus * 1000).into();
+        spec["gpus"] = (self.gpus.len() as u64 * 1000).into();
+        spec["memory"] = self.memory.into();
+        spec["disk"] = self.disk.into();
+    }
+}
+
+/// Returns total memory in bytes from the content of `/proc/meminfo`.
+fn parse_meminfo(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemTotal:"))?;
+    let kilobytes = line.trim().strip_suffix("kB")?.trim().parse::<u64>().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Returns `true` if PCI device class is a display controller (VGA or 3D).
+fn is_gpu_class(class: &str) -> bool {
+    let class = class.trim().trim_start_matches("0x");
+    class.starts_with("0300") || class.starts_with("0302")
+}
+
+/// Returns sorted PCI addresses of GPUs which are in an IOMMU group and can be bound to VFIO.
+fn vfio_gpus(devices: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(devices) else {
+        return vec![];
+    };
+    let mut gpus = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let path = entry.path();
+            std::fs::read_to_string(path.join("class")).is_ok_and(|class| is_gpu_class(&class))
+                && path.join("iommu_group").exists()
+        })
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    gpus.sort();
+    gpus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       32768000 kB\nMemFree:         1024 kB\n";
+        assert_eq!(parse_meminfo(meminfo), Some(32768000 * 1024));
+        assert_eq!(parse_meminfo("MemFree: 1 kB\n"), None);
+    }
+
+    #[test]
+    fn test_is_gpu_class() {
+        assert!(is_gpu_class("0x030000\n"));
+        assert!(is_gpu_class("0x030200"));
+        assert!(!is_gpu_class("0x020000"));
+    }
+}
@@ -0,0 +1,395 @@
+//! Local keystore with keys encrypted at rest.
+//!
+//! Every key is stored in a separate JSON file `<name>.json` in the keystore directory.
+//! Secret (mnemonic or private key) is encrypted with AES-256-GCM using a key derived
+//! from the passphrase with scrypt. Address and public key are stored in plain text,
+//! so keys can be listed without unlocking them.
+//!
+//! The file layout follows the spirit of Ethereum keystore v3 files.
+//! It is not compatible with Cosmos SDK keyring files.
+
+use bip32::{Mnemonic, XPrv};
+use cosmrs::crypto::secp256k1::SigningKey;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+
+use crate::config::Config;
+
+/// Environment variable overriding keystore directory.
+pub const KEYSTORE_DIR_ENV: &str = "GEVULOT_KEYSTORE";
+
+/// Environment variable to take keystore passphrase from instead of prompting it.
+pub const PASSPHRASE_ENV: &str = "GEVULOT_KEYSTORE_PASSPHRASE";
+
+/// Default BIP-44 derivation path for Cosmos keys.
+pub const DEFAULT_HD_PATH: &str = "m/44'/118'/0'/0/0";
+
+/// Returns BIP-44 derivation path for Cosmos keys with the account and address index.
+pub fn hd_path(account: u32, index: u32) -> String {
+    format!("m/44'/118'/{}'/0/{}", account, index)
+}
+
+const KDF: &str = "scrypt";
+const CIPHER: &str = "aes-256-gcm";
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u64 = 8;
+const SCRYPT_P: u64 = 1;
+const SCRYPT_MAX_MEM: u64 = 256 * 1024 * 1024;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Secret material of the key.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum KeySecret {
+    /// BIP-39 mnemonic with optional password.
+    Mnemonic { phrase: String, password: String },
+    /// Hex-encoded secp256k1 private key.
+    PrivateKey { hex: String },
+}
+
+impl KeySecret {
+    /// Derives signing key from the secret.
+    pub fn signing_key(&self) -> Result<SigningKey, Box<dyn std::error::Error>> {
+        self.signing_key_at(DEFAULT_HD_PATH)
+    }
+
+    /// Derives signing key from the secret using the derivation path.
+    pub fn signing_key_at(&self, hd_path: &str) -> Result<SigningKey, Box<dyn std::error::Error>> {
+        Ok(SigningKey::from_slice(&self.private_key_at(hd_path)?)?)
+    }
+
+    /// Derives raw private key from the secret using the derivation path.
+    ///
+    /// Private keys cannot be derived further, so only the default path is accepted for them.
+    pub fn private_key_at(&self, hd_path: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        match self {
+            KeySecret::Mnemonic { phrase, password } => {
+                let mnemonic = Mnemonic::new(phrase, bip32::Language::English)?;
+                let seed = mnemonic.to_seed(password);
+                let child_xprv = XPrv::derive_from_path(&seed, &hd_path.parse()?)?;
+                Ok(child_xprv.private_key().to_bytes().to_vec())
+            }
+            KeySecret::PrivateKey { hex } => {
+                if hd_path != DEFAULT_HD_PATH {
+                    return Err("derivation path can only be used with a mnemonic".into());
+                }
+                Ok(hex::decode(hex)?)
+            }
+        }
+    }
+}
+
+/// Encryption parameters and encrypted secret.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Crypto {
+    kdf: String,
+    log_n: u8,
+    r: u64,
+    p: u64,
+    salt: String,
+    cipher: String,
+    nonce: String,
+    ciphertext: String,
+    tag: String,
+}
+
+/// Key stored in the keystore.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StoredKey {
+    /// Name of the key.
+    pub name: String,
+
+    /// Address of the account.
+    pub address: String,
+
+    /// Hex-encoded compressed public key.
+    pub pub_key: String,
+
+    crypto: Crypto,
+}
+
+impl StoredKey {
+    /// Encrypts secret with the passphrase.
+    pub fn new(
+        name: &str,
+        secret: &KeySecret,
+        account_prefix: &str,
+        passphrase: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let sk = secret.signing_key()?;
+        let public_key = sk.public_key();
+        let address = public_key
+            .account_id(account_prefix)
+            .map_err(|err| err.to_string())?
+            .to_string();
+
+        let mut salt = [0u8; SALT_LEN];
+        openssl::rand::rand_bytes(&mut salt)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        openssl::rand::rand_bytes(&mut nonce)?;
+        let key = derive_key(passphrase, &salt, SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P)?;
+
+        let plaintext = serde_json::to_vec(secret)?;
+        let mut tag = [0u8; TAG_LEN];
+        let ciphertext = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(&nonce),
+            name.as_bytes(),
+            &plaintext,
+            &mut tag,
+        )?;
+
+        Ok(Self {
+            name: name.to_string(),
+            address,
+            pub_key: hex::encode(public_key.to_bytes()),
+            crypto: Crypto {
+                kdf: KDF.to_string(),
+                log_n: SCRYPT_LOG_N,
+                r: SCRYPT_R,
+                p: SCRYPT_P,
+                salt: hex::encode(salt),
+                cipher: CIPHER.to_string(),
+                nonce: hex::encode(nonce),
+                ciphertext: hex::encode(ciphertext),
+                tag: hex::encode(tag),
+            },
+        })
+    }
+
+    /// Decrypts secret with the passphrase.
+    pub fn unlock(&self, passphrase: &str) -> Result<KeySecret, Box<dyn std::error::Error>> {
+        let crypto = &self.crypto;
+        if crypto.kdf != KDF || crypto.cipher != CIPHER {
+            return Err(format!(
+                "unsupported key encryption: {}/{}",
+                crypto.kdf, crypto.cipher
+            )
+            .into());
+        }
+        let key = derive_key(
+            passphrase,
+            &hex::decode(&crypto.salt)?,
+            crypto.log_n,
+            crypto.r,
+            crypto.p,
+        )?;
+        let plaintext = decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(&hex::decode(&crypto.nonce)?),
+            self.name.as_bytes(),
+            &hex::decode(&crypto.ciphertext)?,
+            &hex::decode(&crypto.tag)?,
+        )
+        .map_err(|_| format!("failed to unlock key '{}': wrong passphrase?", self.name))?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
+
+/// Derives encryption key from the passphrase.
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    log_n: u8,
+    r: u64,
+    p: u64,
+) -> Result<[u8; 32], Box<dyn std::error::Error>> {
+    let mut key = [0u8; 32];
+    openssl::pkcs5::scrypt(
+        passphrase.as_bytes(),
+        salt,
+        1 << log_n,
+        r,
+        p,
+        SCRYPT_MAX_MEM,
+        &mut key,
+    )?;
+    Ok(key)
+}
+
+/// Directory-based keystore.
+pub struct Keystore {
+    dir: PathBuf,
+}
+
+impl Keystore {
+    /// Opens keystore in the default location.
+    ///
+    /// Keys are stored in `keys` directory next to the configuration file.
+    /// Can be overridden with `GEVULOT_KEYSTORE` environment variable.
+    pub fn open() -> Self {
+        let dir = std::env::var_os(KEYSTORE_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                Config::path()
+                    .parent()
+                    .map(|dir| dir.join("keys"))
+                    .unwrap_or(PathBuf::from("keys"))
+            });
+        Self { dir }
+    }
+
+    fn key_path(&self, name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+            || name.starts_with('.')
+        {
+            return Err(format!(
+                "invalid key name '{}': only letters, digits, '-', '_' and '.' are allowed",
+                name
+            )
+            .into());
+        }
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+
+    /// Returns `true` if the key exists.
+    pub fn contains(&self, name: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(self.key_path(name)?.exists())
+    }
+
+    /// Stores the key. Fails if the key with the same name already exists.
+    pub fn add(&self, key: &StoredKey) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.key_path(&key.name)?;
+        fs::create_dir_all(&self.dir)?;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(0o600)
+            .open(&path)
+            .map_err(|err| match err.kind() {
+                std::io::ErrorKind::AlreadyExists => format!("key '{}' already exists", key.name),
+                _ => format!("failed to create {}: {}", path.display(), err),
+            })?;
+        file.write_all(serde_json::to_string_pretty(key)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Reads the key.
+    pub fn get(&self, name: &str) -> Result<StoredKey, Box<dyn std::error::Error>> {
+        let path = self.key_path(name)?;
+        let content = fs::read_to_string(&path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => format!("key '{}' not found", name),
+            _ => format!("failed to read {}: {}", path.display(), err),
+        })?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Lists all keys sorted by name.
+    pub fn list(&self) -> Result<Vec<StoredKey>, Box<dyn std::error::Error>> {
+        if !self.dir.is_dir() {
+            return Ok(vec![]);
+        }
+        let mut keys = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                keys.push(serde_json::from_str::<StoredKey>(&fs::read_to_string(
+                    &path,
+                )?)?);
+            }
+        }
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(keys)
+    }
+
+    /// Deletes the key.
+    pub fn delete(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.key_path(name)?;
+        fs::remove_file(&path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => format!("key '{}' not found", name),
+            _ => format!("failed to delete {}: {}", path.display(), err),
+        })?;
+        Ok(())
+    }
+
+    /// Reads the key and unlocks it with the passphrase from environment or terminal prompt.
+    pub fn unlock(&self, name: &str) -> Result<KeySecret, Box<dyn std::error::Error>> {
+        let key = self.get(name)?;
+        let passphrase = read_passphrase(&format!("Enter passphrase for key '{}': ", name))?;
+        key.unlock(&passphrase)
+    }
+}
+
+/// Reads keystore passphrase from environment or prompts it without echo.
+pub fn read_passphrase(prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    crate::utils::read_secret(prompt).map_err(|err| passphrase_hint(err).into())
+}
+
+/// Adds a hint about the passphrase environment variable to the prompt error.
+fn passphrase_hint(err: Box<dyn std::error::Error>) -> String {
+    format!("{} (the passphrase can be set in {})", err, PASSPHRASE_ENV)
+}
+
+/// Reads new keystore passphrase from environment or prompts it twice without echo.
+pub fn read_new_passphrase() -> Result<String, Box<dyn std::error::Error>> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    let passphrase = crate::utils::read_secret("Enter passphrase to encrypt the key: ")
+        .map_err(passphrase_hint)?;
+    if passphrase.is_empty() {
+        return Err("passphrase must not be empty".into());
+    }
+    let repeated = crate::utils::read_secret("Repeat the passphrase: ")?;
+    if passphrase != repeated {
+        return Err("passphrases do not match".into());
+    }
+    Ok(passphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hd_path, KeySecret, StoredKey, DEFAULT_HD_PATH};
+
+    const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    const PRIVATE_KEY: &str = "4f3edf983ac636a65a842ce7c78d9aa706d3b113bce9c46f30d7d21715b23b1d";
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let secret = KeySecret::PrivateKey {
+            hex: PRIVATE_KEY.to_string(),
+        };
+        let key = StoredKey::new("test", &secret, "gvlt", "passphrase").unwrap();
+        assert!(key.address.starts_with("gvlt1"));
+        match key.unlock("passphrase").unwrap() {
+            KeySecret::PrivateKey { hex } => assert_eq!(hex, PRIVATE_KEY),
+            _ => panic!("unexpected secret type"),
+        }
+        assert!(key.unlock("wrong").is_err());
+    }
+
+    #[test]
+    fn test_hd_path() {
+        assert_eq!(hd_path(0, 0), DEFAULT_HD_PATH);
+        assert_eq!(hd_path(2, 7), "m/44'/118'/2'/0/7");
+
+        let secret = KeySecret::Mnemonic {
+            phrase: MNEMONIC.to_string(),
+            password: String::new(),
+        };
+        let default_key = secret.private_key_at(DEFAULT_HD_PATH).unwrap();
+        assert_eq!(secret.private_key_at(&hd_path(0, 0)).unwrap(), default_key);
+        assert_ne!(secret.private_key_at(&hd_path(0, 1)).unwrap(), default_key);
+        assert_ne!(secret.private_key_at(&hd_path(1, 0)).unwrap(), default_key);
+
+        let secret = KeySecret::PrivateKey {
+            hex: PRIVATE_KEY.to_string(),
+        };
+        assert!(secret.private_key_at(DEFAULT_HD_PATH).is_ok());
+        assert!(secret.private_key_at(&hd_path(0, 1)).is_err());
+    }
+}
@@ -10,10 +10,18 @@ use std::path::PathBuf;
 
 mod builders;
 mod commands;
+mod config;
+mod hardware;
+mod keystore;
+mod query;
+mod table;
+mod template;
+mod unixfs;
 mod utils;
 mod version;
 
 use commands::*;
+use keystore::DEFAULT_HD_PATH;
 use utils::*;
 use version::get_long_version;
 
@@ -24,51 +32,86 @@ pub struct Cli {
     #[command(subcommand)]
     command: Command,
 
-    /// Sets the output format.
-    #[arg(
-        global = true,
-        short = 'F',
-        long,
-        default_value_t,
-        env = "GEVULOT_FORMAT"
-    )]
-    format: OutputFormat,
+    /// Output options.
+    #[command(flatten)]
+    output: OutputArgs,
 }
 
 impl Cli {
     /// Match the command and run it.
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
         match &self.command {
-            Command::Worker(command) => command.run(self.format).await,
-            Command::Task(command) => command.run(self.format).await,
-            Command::Pin(command) => command.run(self.format).await,
-            Command::Workflow(command) => command.run().await,
+            Command::Worker(command) => command.run(&self.output).await,
+            Command::Task(command) => command.run(&self.output).await,
+            Command::Pin(command) => command.run(&self.output).await,
+            Command::Workflow(command) => command.run(&self.output).await,
             Command::Keygen {
                 file,
                 password,
                 account_prefix,
-            } => generate_key(file.path_ref(), password, account_prefix, self.format).await,
+                hd_path,
+            } => {
+                generate_key(
+                    file.path_ref(),
+                    password,
+                    account_prefix,
+                    hd_path,
+                    &self.output,
+                )
+                .await
+            }
             Command::ComputeKey {
                 mnemonic,
                 private_key,
                 password,
                 account_prefix,
-            } => compute_key(mnemonic, private_key, password, account_prefix, self.format).await,
+                hd_path,
+            } => {
+                compute_key(
+                    mnemonic,
+                    private_key,
+                    password,
+                    account_prefix,
+                    hd_path,
+                    &self.output,
+                )
+                .await
+            }
             Command::Send {
                 chain_args,
                 amount,
+                raw_denom,
                 receiver,
-            } => send_tokens(chain_args, *amount, receiver, self.format).await,
+                memo,
+                yes,
+            } => {
+                let chain_args = &chain_args.unlock()?;
+                let value =
+                    send::send_tokens(chain_args, amount, *raw_denom, receiver, memo, *yes).await?;
+                print_object(&self.output, &value)
+            }
+            Command::Account(command) => command.run(&self.output).await,
             Command::AccountInfo {
                 chain_args,
                 address,
-            } => account_info(chain_args, address, self.format).await,
+                denom,
+            } => {
+                let chain_args = &chain_args.resolve()?;
+                let value = account::account_info(chain_args, address, denom.as_deref()).await?;
+                print_object(&self.output, &value)
+            }
             Command::GenerateCompletion { shell, file } => {
                 generate_completion(*shell, file.path_ref()).await
             }
-            Command::Sudo(command) => command.run(self.format).await,
-            Command::Build(build_args) => build_args.run(self.format).await,
-            Command::LocalRun(run_args) => run_args.run(self.format).await,
+            Command::Sudo(command) => command.run(&self.output).await,
+            Command::Build(build_args) => build_args.run(&self.output).await,
+            Command::LocalRun(run_args) => run_args.run(&self.output).await,
+            Command::Config(command) => command.run(&self.output).await,
+            Command::Keys(command) => command.run(&self.output).await,
+            Command::Tx(command) => command.run(&self.output).await,
+            Command::Validate(validate_args) => validate_args.run(&self.output).await,
+            Command::Apply(apply_args) => apply_args.run(&self.output).await,
+            Command::Delete(delete_args) => delete_args.run(&self.output).await,
         }
     }
 }
@@ -101,6 +144,9 @@ pub enum Command {
         /// The account prefix to use for the key.
         #[arg(short, long, default_value_t = String::from("gvlt"))]
         account_prefix: String,
+
+        #[command(flatten)]
+        hd_path: HdPathArgs,
     },
 
     /// Compute a key.
@@ -120,6 +166,9 @@ pub enum Command {
         /// The account prefix to use for the key.
         #[arg(short, long, default_value_t = String::from("gvlt"))]
         account_prefix: String,
+
+        #[command(flatten)]
+        hd_path: HdPathArgs,
     },
 
     /// Send tokens to a receiver on the Gevulot network.
@@ -127,20 +176,43 @@ pub enum Command {
         #[command(flatten)]
         chain_args: ChainArgs,
 
-        /// The amount of tokens to send.
-        amount: u128,
+        /// The amount of tokens to send with its denomination, e.g. 1.5gvlt or 1500000ugvlt.
+        ///
+        /// Amounts are converted into base units using denomination metadata of the chain.
+        amount: send::TokenAmount,
+
+        /// Send the amount as is in a denomination without metadata on the chain.
+        ///
+        /// The amount must be in base units of the denomination.
+        #[arg(long)]
+        raw_denom: bool,
 
         /// The receiver address.
         receiver: String,
+
+        /// Memo of the transaction.
+        #[arg(long, default_value_t)]
+        memo: String,
+
+        /// Do not ask for confirmation. Required if stdin is not a terminal.
+        #[arg(short, long)]
+        yes: bool,
     },
 
-    /// Get the balance of the given account.
+    /// Commands related to accounts.
+    Account(account::Command),
+
+    /// Get balances, delegations, rewards and unbonding delegations of the given account.
     AccountInfo {
         #[command(flatten)]
         chain_args: ChainArgs,
 
         /// The address to get the balance of.
         address: String,
+
+        /// Only show amounts in this denomination.
+        #[arg(long)]
+        denom: Option<String>,
     },
 
     /// Generate shell completion scripts.
@@ -161,6 +233,24 @@ pub enum Command {
 
     /// Run VM locally.
     LocalRun(local_run::RunArgs),
+
+    /// Manage configuration profiles with chain connection settings.
+    Config(commands::config::Command),
+
+    /// Manage keys in the local encrypted keystore.
+    Keys(keys::Command),
+
+    /// Sign, broadcast and inspect transactions.
+    Tx(tx::Command),
+
+    /// Validate task, worker, pin and workflow files without submitting them.
+    Validate(validate::ValidateArgs),
+
+    /// Create or update tasks, workers and pins described in files.
+    Apply(apply::ApplyArgs),
+
+    /// Delete tasks, workers and pins described in files.
+    Delete(apply::DeleteArgs),
 }
 
 /// Main entry point for the Gevulot Control CLI application.
@@ -173,68 +263,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Cli::parse().run().await
 }
 
-/// Sends tokens to a receiver on the Gevulot network.
-async fn send_tokens(
-    chain_args: &ChainArgs,
-    amount: u128,
-    receiver: &str,
-    format: OutputFormat,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let client = connect_to_gevulot(chain_args).await?;
-    client
-        .base_client
-        .write()
-        .await
-        .token_transfer(receiver, amount)
-        .await?;
-
-    let output = serde_json::json!({
-        "success": true,
-        "amount": amount,
-        "receiver": receiver
-    });
-
-    print_object(format, &output)?;
-
-    Ok(())
-}
-
-/// Retrieves and displays account information for a given address.
-async fn account_info(
-    chain_args: &ChainArgs,
-    address: &str,
-    format: OutputFormat,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let client = connect_to_gevulot(chain_args).await?;
-    let account = client
-        .base_client
-        .write()
-        .await
-        .get_account(address)
-        .await?;
-    let balance = client
-        .base_client
-        .write()
-        .await
-        .get_account_balance(address)
-        .await?;
-
-    let output = serde_json::json!({
-        "account_number": account.account_number,
-        "sequence": account.sequence,
-        "balance": balance.amount.to_string()
-    });
-
-    print_object(format, &output)?;
-    Ok(())
-}
-
 /// Generates a new key and optionally saves it to a file.
 async fn generate_key(
     path: Option<&PathBuf>,
     password: &str,
     account_prefix: &str,
-    format: OutputFormat,
+    hd_path: &HdPathArgs,
+    output: &OutputArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Generate random Mnemonic using the default language (English)
     let mnemonic = Mnemonic::random(OsRng, Default::default());
@@ -243,7 +278,7 @@ async fn generate_key(
     let seed = mnemonic.to_seed(password);
 
     // Derive a child `XPrv` using the provided BIP32 derivation path
-    let child_path = "m/44'/118'/0'/0/0";
+    let child_path = hd_path.path().unwrap_or(DEFAULT_HD_PATH.to_string());
     let child_xprv = XPrv::derive_from_path(&seed, &child_path.parse()?)?;
 
     // Get the `XPub` associated with `child_xprv`.
@@ -266,10 +301,11 @@ async fn generate_key(
     let private_key = child_xprv.private_key().to_bytes();
     let private_key_hex = hex::encode(private_key);
 
-    let output = serde_json::json!({
+    let value = serde_json::json!({
         "account_id": account_id,
         "mnemonic": phrase,
         "private_key": private_key_hex,
+        "hd_path": child_path,
     });
 
     if let Some(file) = path {
@@ -277,25 +313,30 @@ async fn generate_key(
         file.write_all(phrase.as_bytes())?;
     }
 
-    print_object(format, &output)?;
+    print_object(output, &value)?;
 
     Ok(())
 }
 
-/// Generates a new key and optionally saves it to a file.
+/// Computes the address of the key derived from mnemonic or private key.
 async fn compute_key(
     mnemonic: &Option<String>,
     private_key: &Option<String>,
     password: &str,
     prefix: &str,
-    format: OutputFormat,
+    hd_path: &HdPathArgs,
+    output: &OutputArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let child_path = hd_path.path();
     let sk = if let Some(private_key) = private_key {
+        if child_path.is_some() {
+            return Err("--account, --index and --hd-path can only be used with a mnemonic".into());
+        }
         SigningKey::from_slice(hex::decode(private_key)?.as_slice())?
     } else if let Some(mnemonic) = mnemonic {
         let mnemonic = Mnemonic::new(mnemonic, bip32::Language::English)?;
         let seed = mnemonic.to_seed(password);
-        let child_path = "m/44'/118'/0'/0/0";
+        let child_path = child_path.as_deref().unwrap_or(DEFAULT_HD_PATH);
         let child_xprv = XPrv::derive_from_path(&seed, &child_path.parse()?)?;
         SigningKey::from_slice(&child_xprv.private_key().to_bytes())?
     } else {
@@ -304,8 +345,11 @@ async fn compute_key(
 
     let account_id = sk.public_key().account_id(prefix).unwrap();
 
-    let output = serde_json::json!({ "account_id": account_id });
-    print_object(format, &output)?;
+    let mut value = serde_json::json!({ "account_id": account_id });
+    if let Some(child_path) = child_path {
+        value["hd_path"] = child_path.into();
+    }
+    print_object(output, &value)?;
     Ok(())
 }
 
@@ -0,0 +1,228 @@
+//! Field selection on JSON values using a subset of jq and JSONPath syntax.
+//!
+//! Supported expressions:
+//!
+//! - `.` or `$` - the whole value
+//! - `.field` or `$.field` - field of an object
+//! - `.["field"]` - field of an object with arbitrary characters in its name
+//! - `.[N]` - element of an array (negative indices count from the end)
+//! - `.[]` or `.[*]` - every element of an array or every value of an object
+//!
+//! Segments can be chained, e.g. `.[].metadata.id` or `$[*].status.state`.
+
+use serde_json::Value;
+use std::fmt;
+
+/// Segment of a query path.
+#[derive(Clone, Debug, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(i64),
+    Iterate,
+}
+
+/// Parsed query expression.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    expr: String,
+    segments: Vec<Segment>,
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.expr)
+    }
+}
+
+impl Query {
+    /// Parses query expression.
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let trimmed = expr.trim();
+        let rest = trimmed.strip_prefix('$').unwrap_or(trimmed);
+        let chars = rest.chars().collect::<Vec<_>>();
+        let mut segments = vec![];
+        let mut pos = 0;
+
+        while pos < chars.len() {
+            match chars[pos] {
+                '.' => {
+                    pos += 1;
+                    let start = pos;
+                    while pos < chars.len() && is_ident_char(chars[pos]) {
+                        pos += 1;
+                    }
+                    if start != pos {
+                        segments.push(Segment::Field(chars[start..pos].iter().collect()));
+                    } else if pos < chars.len() && chars[pos] != '[' {
+                        return Err(format!(
+                            "unexpected '{}' at position {} in query '{}'",
+                            chars[pos],
+                            pos,
+                            expr.trim()
+                        ));
+                    }
+                }
+                '[' => {
+                    let end = chars[pos..]
+                        .iter()
+                        .position(|c| *c == ']')
+                        .map(|offset| pos + offset)
+                        .ok_or_else(|| format!("unclosed '[' in query '{}'", expr.trim()))?;
+                    let inner = chars[pos + 1..end].iter().collect::<String>();
+                    let inner = inner.trim();
+                    let segment = if inner.is_empty() || inner == "*" {
+                        Segment::Iterate
+                    } else if let Some(field) = unquote(inner) {
+                        Segment::Field(field.to_string())
+                    } else {
+                        Segment::Index(inner.parse().map_err(|_| {
+                            format!("invalid index '{}' in query '{}'", inner, expr.trim())
+                        })?)
+                    };
+                    segments.push(segment);
+                    pos = end + 1;
+                }
+                c => {
+                    return Err(format!(
+                        "unexpected '{}' at position {} in query '{}'",
+                        c,
+                        pos,
+                        expr.trim()
+                    ))
+                }
+            }
+        }
+
+        Ok(Self {
+            expr: trimmed.to_string(),
+            segments,
+        })
+    }
+
+    /// Applies query to the value.
+    ///
+    /// If query contains iteration, all selected values are returned as an array.
+    /// Missing fields are returned as `null`.
+    pub fn apply(&self, value: &Value) -> Value {
+        let mut current = vec![value.clone()];
+        let mut multiple = false;
+        for segment in &self.segments {
+            current = match segment {
+                Segment::Field(name) => current
+                    .into_iter()
+                    .map(|value| value.get(name).cloned().unwrap_or(Value::Null))
+                    .collect(),
+                Segment::Index(idx) => current
+                    .into_iter()
+                    .map(|value| {
+                        value
+                            .as_array()
+                            .and_then(|array| {
+                                let idx = if *idx < 0 {
+                                    array.len().checked_sub(idx.unsigned_abs() as usize)?
+                                } else {
+                                    *idx as usize
+                                };
+                                array.get(idx).cloned()
+                            })
+                            .unwrap_or(Value::Null)
+                    })
+                    .collect(),
+                Segment::Iterate => {
+                    multiple = true;
+                    current
+                        .into_iter()
+                        .flat_map(|value| match value {
+                            Value::Array(array) => array,
+                            Value::Object(object) => object.into_iter().map(|(_, v)| v).collect(),
+                            _ => vec![],
+                        })
+                        .collect()
+                }
+            };
+        }
+        if multiple {
+            Value::Array(current)
+        } else {
+            current.pop().unwrap_or(Value::Null)
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Strips single or double quotes from the string if present.
+fn unquote(s: &str) -> Option<&str> {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .or_else(|| s.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
+}
+
+/// Converts value to raw string representation if it is a scalar or an array of scalars.
+///
+/// Strings are returned without quotes, array elements are separated by new lines.
+pub fn raw_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(_) | Value::Bool(_) | Value::Null => Some(value.to_string()),
+        Value::Array(array) => array
+            .iter()
+            .map(|value| match value {
+                Value::Array(_) | Value::Object(_) => None,
+                _ => raw_string(value),
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|lines| lines.join("\n")),
+        Value::Object(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{raw_string, Query};
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Query::parse(".foo[").is_err());
+        assert!(Query::parse(".foo[bar]").is_err());
+        assert!(Query::parse("foo").is_err());
+        assert!(Query::parse(".foo..bar").is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let value = json!({
+            "task_id": "task-1",
+            "items": [
+                { "metadata": { "id": "a" } },
+                { "metadata": { "id": "b" } },
+            ],
+            "weird.key": 1,
+        });
+        let query = |expr: &str| Query::parse(expr).unwrap().apply(&value);
+
+        assert_eq!(query("."), value);
+        assert_eq!(query("$"), value);
+        assert_eq!(query(".task_id"), json!("task-1"));
+        assert_eq!(query("$.task_id"), json!("task-1"));
+        assert_eq!(query(".items[1].metadata.id"), json!("b"));
+        assert_eq!(query(".items[-1].metadata.id"), json!("b"));
+        assert_eq!(query(".items[].metadata.id"), json!(["a", "b"]));
+        assert_eq!(query("$.items[*].metadata.id"), json!(["a", "b"]));
+        assert_eq!(query(".[\"weird.key\"]"), json!(1));
+        assert_eq!(query(".missing.field"), json!(null));
+        assert_eq!(query(".items[5]"), json!(null));
+    }
+
+    #[test]
+    fn test_raw_string() {
+        assert_eq!(raw_string(&json!("task-1")), Some("task-1".to_string()));
+        assert_eq!(raw_string(&json!(42)), Some("42".to_string()));
+        assert_eq!(raw_string(&json!(["a", "b"])), Some("a\nb".to_string()));
+        assert_eq!(raw_string(&json!({ "a": 1 })), None);
+        assert_eq!(raw_string(&json!([["a"]])), None);
+    }
+}
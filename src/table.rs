@@ -0,0 +1,291 @@
+//! Human-readable table rendering of JSON values.
+
+use serde_json::Value;
+
+use crate::utils::get_field;
+
+/// Separator between table columns.
+const COLUMN_SEPARATOR: &str = "   ";
+
+/// Placeholder for missing values.
+const NONE: &str = "<none>";
+
+/// Table column: header and path to the field in JSON representation of the object.
+type Column = (String, String);
+
+/// Default columns for known object kinds.
+///
+/// Wide columns are appended to the default ones in wide output.
+fn default_columns(kind: &str, wide: bool) -> Option<Vec<Column>> {
+    let (columns, wide_columns): (&[(&str, &str)], &[(&str, &str)]) = match kind {
+        "Task" => (
+            &[
+                ("ID", "metadata.id"),
+                ("NAME", "metadata.name"),
+                ("STATE", "status.state"),
+                ("WORKER", "status.activeWorker"),
+                ("EXIT CODE", "status.exitCode"),
+            ],
+            &[
+                ("CREATOR", "metadata.creator"),
+                ("IMAGE", "spec.image"),
+                ("CPUS", "spec.resources.cpus"),
+                ("GPUS", "spec.resources.gpus"),
+                ("MEMORY", "spec.resources.memory"),
+                ("TIME", "spec.resources.time"),
+                ("CREATED", "status.createdAt"),
+                ("LABELS", "metadata.labels"),
+            ],
+        ),
+        "Worker" => (
+            &[
+                ("ID", "metadata.id"),
+                ("NAME", "metadata.name"),
+                ("CPUS", "spec.cpus"),
+                ("GPUS", "spec.gpus"),
+                ("MEMORY", "spec.memory"),
+                ("DISK", "spec.disk"),
+            ],
+            &[
+                ("CREATOR", "metadata.creator"),
+                ("TAGS", "metadata.tags"),
+                ("LABELS", "metadata.labels"),
+                ("DESCRIPTION", "metadata.description"),
+            ],
+        ),
+        "Pin" => (
+            &[
+                ("CID", "spec.cid"),
+                ("NAME", "metadata.name"),
+                ("BYTES", "spec.bytes"),
+                ("TIME", "spec.time"),
+                ("REDUNDANCY", "spec.redundancy"),
+            ],
+            &[
+                ("ID", "metadata.id"),
+                ("CREATOR", "metadata.creator"),
+                ("WORKERS", "status.assignedWorkers"),
+                ("TAGS", "metadata.tags"),
+                ("LABELS", "metadata.labels"),
+            ],
+        ),
+        "Workflow" => (
+            &[
+                ("ID", "metadata.id"),
+                ("NAME", "metadata.name"),
+                ("STATE", "status.state"),
+            ],
+            &[
+                ("CREATOR", "metadata.creator"),
+                ("LABELS", "metadata.labels"),
+            ],
+        ),
+        _ => return None,
+    };
+    let columns = columns
+        .iter()
+        .chain(wide.then_some(wide_columns).unwrap_or_default())
+        .map(|(header, path)| (header.to_string(), path.to_string()))
+        .collect();
+    Some(columns)
+}
+
+/// Builds column from its path. Header is the last segment of the path in upper case.
+fn column_from_path(path: &str) -> Column {
+    let header = path.rsplit('.').next().unwrap_or(path).to_ascii_uppercase();
+    (header, path.to_string())
+}
+
+/// Renders JSON value as a human-readable table.
+///
+/// Arrays of objects are rendered as aligned columns. Columns are taken from `columns`
+/// if provided, otherwise default columns for the kind of objects are used.
+/// Other values are rendered as key/value pairs.
+pub fn render(value: &Value, columns: &[String], wide: bool) -> String {
+    match value {
+        Value::Array(items) => render_list(items, columns, wide),
+        Value::Object(_) => render_key_value(value, columns),
+        _ => format!("{}\n", format_cell(value)),
+    }
+}
+
+/// Renders list of objects as aligned columns.
+fn render_list(items: &[Value], columns: &[String], wide: bool) -> String {
+    let columns: Vec<Column> = if !columns.is_empty() {
+        columns.iter().map(|path| column_from_path(path)).collect()
+    } else if let Some(columns) = items
+        .first()
+        .and_then(|item| item.get("kind"))
+        .and_then(Value::as_str)
+        .and_then(|kind| default_columns(kind, wide))
+    {
+        columns
+    } else {
+        // Unknown objects: use their top-level scalar fields.
+        items
+            .first()
+            .and_then(Value::as_object)
+            .map(|object| {
+                object
+                    .iter()
+                    .filter(|(_, value)| wide || (!value.is_object() && !value.is_array()))
+                    .map(|(key, _)| column_from_path(key))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    if columns.is_empty() {
+        return items
+            .iter()
+            .map(|item| format!("{}\n", format_cell(item)))
+            .collect();
+    }
+
+    let mut rows = vec![columns
+        .iter()
+        .map(|(header, _)| header.clone())
+        .collect::<Vec<_>>()];
+    for item in items {
+        rows.push(
+            columns
+                .iter()
+                .map(|(_, path)| get_field(item, path).map_or(NONE.to_string(), format_cell))
+                .collect(),
+        );
+    }
+    render_rows(&rows)
+}
+
+/// Renders an object as key/value pairs. Nested fields are flattened into dot-separated keys.
+fn render_key_value(value: &Value, columns: &[String]) -> String {
+    let mut rows = vec![];
+    if columns.is_empty() {
+        flatten(value, String::new(), &mut rows);
+    } else {
+        for path in columns {
+            let cell = get_field(value, path).map_or(NONE.to_string(), format_cell);
+            rows.push(vec![path.clone(), cell]);
+        }
+    }
+    render_rows(&rows)
+}
+
+/// Flattens nested objects into rows of dot-separated keys and values.
+fn flatten(value: &Value, prefix: String, rows: &mut Vec<Vec<String>>) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(value, key, rows);
+            }
+        }
+        _ => rows.push(vec![prefix, format_cell(value)]),
+    }
+}
+
+/// Renders rows as aligned columns.
+fn render_rows(rows: &[Vec<String>]) -> String {
+    let mut widths = vec![];
+    for row in rows {
+        for (idx, cell) in row.iter().enumerate() {
+            let width = cell.chars().count();
+            match widths.get_mut(idx) {
+                Some(w) if *w < width => *w = width,
+                Some(_) => {}
+                None => widths.push(width),
+            }
+        }
+    }
+
+    let mut result = String::new();
+    for row in rows {
+        let last = row.len().saturating_sub(1);
+        for (idx, cell) in row.iter().enumerate() {
+            result.push_str(cell);
+            if idx != last {
+                let padding = widths[idx] - cell.chars().count();
+                result.push_str(&" ".repeat(padding));
+                result.push_str(COLUMN_SEPARATOR);
+            }
+        }
+        result.push('\n');
+    }
+    result
+}
+
+/// Formats JSON value as a single table cell.
+fn format_cell(value: &Value) -> String {
+    match value {
+        Value::Null => NONE.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.is_empty() => NONE.to_string(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match (item.get("key"), item.get("value")) {
+                // Labels are rendered as KEY=VALUE pairs.
+                (Some(key), Some(value)) => format!("{}={}", format_cell(key), format_cell(value)),
+                _ => format_cell(item),
+            })
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_list_default_columns() {
+        let tasks = json!([
+            {
+                "kind": "Task",
+                "metadata": { "id": "task-1", "name": "first" },
+                "status": { "state": "Done", "activeWorker": "worker-1", "exitCode": 0 },
+            },
+            {
+                "kind": "Task",
+                "metadata": { "id": "task-22", "name": "second" },
+                "status": { "state": "Pending" },
+            },
+        ]);
+        assert_eq!(
+            render(&tasks, &[], false),
+            "ID        NAME     STATE     WORKER     EXIT CODE\n\
+             task-1    first    Done      worker-1   0\n\
+             task-22   second   Pending   <none>     <none>\n"
+        );
+    }
+
+    #[test]
+    fn test_render_list_custom_columns() {
+        let pins = json!([
+            {
+                "kind": "Pin",
+                "metadata": { "labels": [{ "key": "env", "value": "dev" }] },
+                "spec": { "cid": "bafy" },
+            },
+        ]);
+        let columns = ["spec.cid".to_string(), "metadata.labels".to_string()];
+        assert_eq!(
+            render(&pins, &columns, false),
+            "CID    LABELS\nbafy   env=dev\n"
+        );
+    }
+
+    #[test]
+    fn test_render_key_value() {
+        let value = json!({ "status": "success", "task": { "id": "task-1" } });
+        assert_eq!(
+            render(&value, &[], false),
+            "status    success\ntask.id   task-1\n"
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Variable substitution in input documents.
+//!
+//! Two forms of placeholders are supported:
+//!
+//! - `${NAME}` and `${NAME:-default}` like in shell;
+//! - `{{ name }}` and `{{ name | default "value" }}` like in Helm templates.
+//!
+//! Documents are only rendered when templating is requested with `--set`, `--values`,
+//! `--template` or `--template-env`, so plain documents are passed through unchanged.
+//! Variables are taken from `--set`, then from `--values` files and then,
+//! with `--template-env`, from the environment. `$${` is rendered as a literal `${`.
+
+use serde::de::DeserializeOwned;
+use serde_yaml::Value as YamlValue;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::utils::{read_documents, read_input, Document};
+
+/// Template arguments.
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct TemplateArgs {
+    /// Sets a template variable. Can be repeated.
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_assignment)]
+    pub set: Vec<(String, String)>,
+
+    /// Reads template variables from a YAML file. Can be repeated, later files take precedence.
+    ///
+    /// Nested keys are joined with dots, e.g. `resources.cpus`.
+    #[arg(long = "values", value_name = "FILE")]
+    pub values: Vec<PathBuf>,
+
+    /// Renders placeholders even if no variables are set, e.g. to use their defaults.
+    #[arg(long)]
+    pub template: bool,
+
+    /// Takes template variables which are not set otherwise from environment variables.
+    ///
+    /// Values of environment variables end up in submitted documents, so use it with care.
+    #[arg(long)]
+    pub template_env: bool,
+}
+
+impl TemplateArgs {
+    /// Collects variables from values files and `--set` arguments.
+    pub fn variables(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        let mut variables = HashMap::new();
+        for file in &self.values {
+            let content = std::fs::read_to_string(file)
+                .map_err(|err| format!("{}: {}", file.display(), err))?;
+            let value: YamlValue = serde_yaml::from_str(&content)
+                .map_err(|err| format!("{}: {}", file.display(), err))?;
+            flatten("", &value, &mut variables)?;
+        }
+        variables.extend(self.set.iter().cloned());
+        Ok(variables)
+    }
+
+    /// Returns `true` if documents should be rendered.
+    pub fn is_enabled(&self) -> bool {
+        self.template || self.template_env || !self.set.is_empty() || !self.values.is_empty()
+    }
+
+    /// Returns the function looking up variables or `None` if templating is not requested.
+    fn lookup(
+        &self,
+    ) -> Result<Option<impl Fn(&str) -> Option<String>>, Box<dyn std::error::Error>> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+        let variables = self.variables()?;
+        let template_env = self.template_env;
+        Ok(Some(move |name: &str| {
+            variables
+                .get(name)
+                .cloned()
+                .or_else(|| template_env.then(|| std::env::var(name).ok()).flatten())
+        }))
+    }
+
+    /// Reads all documents from the file, directory or stdin and renders them.
+    pub async fn read_documents(
+        &self,
+        path: Option<&Path>,
+    ) -> Result<Vec<Document>, Box<dyn std::error::Error>> {
+        let documents = read_documents(path).await?;
+        let Some(lookup) = self.lookup()? else {
+            return Ok(documents);
+        };
+        documents
+            .into_iter()
+            .map(|document| {
+                let content = render(&document.content, &lookup).map_err(|(line, err)| {
+                    format!("{}:{}: {}", document.file, document.line + line - 1, err)
+                })?;
+                Ok(Document {
+                    content,
+                    ..document
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the file or stdin, renders it and parses the result.
+    pub async fn read_file<T: DeserializeOwned>(
+        &self,
+        path: Option<&Path>,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let content = read_input(path).await?;
+        let Some(lookup) = self.lookup()? else {
+            return Ok(serde_yaml::from_str(&content)?);
+        };
+        let content = render(&content, &lookup).map_err(|(line, err)| {
+            let file = path
+                .map(|path| path.display().to_string())
+                .unwrap_or("-".to_string());
+            format!("{}:{}: {}", file, line, err)
+        })?;
+        Ok(serde_yaml::from_str(&content)?)
+    }
+}
+
+/// Parses `KEY=VALUE` argument.
+fn parse_assignment(arg: &str) -> Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", arg))?;
+    if !is_valid_name(key) {
+        return Err(format!("invalid variable name '{}'", key));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Flattens nested mappings into variables with dot-separated names.
+///
+/// Sequences and other non-scalar values are rendered as JSON.
+fn flatten(
+    prefix: &str,
+    value: &YamlValue,
+    variables: &mut HashMap<String, String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match value {
+        YamlValue::Mapping(mapping) => {
+            for (key, value) in mapping {
+                let key = match key {
+                    YamlValue::String(key) => key.clone(),
+                    YamlValue::Number(key) => key.to_string(),
+                    YamlValue::Bool(key) => key.to_string(),
+                    _ => return Err("values keys must be scalars".into()),
+                };
+                let name = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&name, value, variables)?;
+            }
+        }
+        YamlValue::Null if prefix.is_empty() => {}
+        YamlValue::Null => {
+            variables.insert(prefix.to_string(), String::new());
+        }
+        YamlValue::String(value) => {
+            variables.insert(prefix.to_string(), value.clone());
+        }
+        YamlValue::Number(value) => {
+            variables.insert(prefix.to_string(), value.to_string());
+        }
+        YamlValue::Bool(value) => {
+            variables.insert(prefix.to_string(), value.to_string());
+        }
+        value if prefix.is_empty() => {
+            return Err(format!("values must be a mapping, got {:?}", value).into());
+        }
+        value => {
+            variables.insert(prefix.to_string(), serde_json::to_string(value)?);
+        }
+    }
+    Ok(())
+}
+
+/// Checks if the string is a valid variable name.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// Substitutes all placeholders in the content.
+///
+/// Returns the line number (starting from 1) and the message on error.
+pub fn render(
+    content: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, (usize, String)> {
+    let mut rendered = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(start) = rest.find(['$', '{']) {
+        rendered.push_str(&rest[..start]);
+        let line = content[..content.len() - rest.len() + start]
+            .matches('\n')
+            .count()
+            + 1;
+        let placeholder = &rest[start..];
+        if let Some(escaped) = placeholder.strip_prefix("$${") {
+            rendered.push_str("${");
+            rest = escaped;
+        } else if let Some(inner) = placeholder.strip_prefix("${") {
+            let end = inner
+                .find('}')
+                .ok_or((line, "unterminated '${'".to_string()))?;
+            let (name, default) = match inner[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default.to_string())),
+                None => (&inner[..end], None),
+            };
+            rendered.push_str(&substitute(name, default, &lookup).map_err(|err| (line, err))?);
+            rest = &inner[end + 1..];
+        } else if let Some(inner) = placeholder.strip_prefix("{{") {
+            let end = inner
+                .find("}}")
+                .ok_or((line, "unterminated '{{'".to_string()))?;
+            let (name, default) = parse_expression(&inner[..end]).map_err(|err| (line, err))?;
+            rendered.push_str(&substitute(name, default, &lookup).map_err(|err| (line, err))?);
+            rest = &inner[end + 2..];
+        } else {
+            rendered.push_str(&placeholder[..1]);
+            rest = &placeholder[1..];
+        }
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Parses `name` or `name | default "value"` expression.
+fn parse_expression(expression: &str) -> Result<(&str, Option<String>), String> {
+    let Some((name, filter)) = expression.split_once('|') else {
+        return Ok((expression.trim(), None));
+    };
+    let default = filter
+        .trim()
+        .strip_prefix("default")
+        .filter(|value| value.starts_with(char::is_whitespace))
+        .ok_or_else(|| format!("unsupported filter '{}'", filter.trim()))?
+        .trim();
+    let unquoted = [('"', '"'), ('\'', '\'')]
+        .iter()
+        .find_map(|(open, close)| default.strip_prefix(*open)?.strip_suffix(*close))
+        .unwrap_or(default);
+    Ok((name.trim(), Some(unquoted.to_string())))
+}
+
+/// Returns the value of the variable or its default.
+fn substitute(
+    name: &str,
+    default: Option<String>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    if !is_valid_name(name) {
+        return Err(format!("invalid variable name '{}'", name));
+    }
+    lookup(name)
+        .or(default)
+        .ok_or_else(|| format!("variable '{}' is not set", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let variables = HashMap::from([
+            ("image".to_string(), "alpine".to_string()),
+            ("resources.cpus".to_string(), "2".to_string()),
+        ]);
+        let lookup = |name: &str| variables.get(name).cloned();
+
+        let content = "image: ${image}\ncpus: {{ resources.cpus }}\nmemory: ${MEMORY:-1 GiB}\n\
+            time: {{ time | default \"1 hour\" }}\nargs: [\"$${HOME}\", \"{x}\"]\n";
+        assert_eq!(
+            render(content, lookup).unwrap(),
+            "image: alpine\ncpus: 2\nmemory: 1 GiB\ntime: 1 hour\nargs: [\"${HOME}\", \"{x}\"]\n"
+        );
+
+        assert_eq!(
+            render("a: 1\nb: ${missing}\n", lookup).unwrap_err(),
+            (2, "variable 'missing' is not set".to_string())
+        );
+        assert_eq!(render("{{ image | upper }}", lookup).unwrap_err().0, 1);
+        assert!(render("${image", lookup).is_err());
+    }
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(
+            parse_assignment("a.b=c=d").unwrap(),
+            ("a.b".to_string(), "c=d".to_string())
+        );
+        assert!(parse_assignment("abc").is_err());
+        assert!(parse_assignment("1a=b").is_err());
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        assert!(!TemplateArgs::default().is_enabled());
+        let args = TemplateArgs {
+            set: vec![("image".to_string(), "alpine".to_string())],
+            ..Default::default()
+        };
+        assert!(args.is_enabled());
+        let lookup = args.lookup().unwrap().unwrap();
+        assert_eq!(lookup("image").as_deref(), Some("alpine"));
+        // Environment is only used with --template-env
+        assert_eq!(lookup("PATH"), None);
+    }
+}
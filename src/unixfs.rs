@@ -0,0 +1,341 @@
+//! Local import of files and directories into UnixFS DAG.
+//!
+//! Content is chunked in the same way as `ipfs add --cid-version 1` does by default:
+//! files are split into 256 KiB raw leaves which are linked into a balanced tree
+//! of dag-pb nodes with at most 174 links, directories are dag-pb nodes linking
+//! their entries sorted by name. All CIDs are CIDv1 with SHA2-256 multihash.
+//!
+//! Large directories are not sharded, so CIDs of directories with thousands of entries
+//! will differ from the ones computed by IPFS.
+
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// Size of file chunks.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Maximum number of links in a file node.
+const MAX_LINKS: usize = 174;
+
+/// Multicodec of raw blocks.
+const RAW: u64 = 0x55;
+
+/// Multicodec of dag-pb blocks.
+const DAG_PB: u64 = 0x70;
+
+/// Multicodec of SHA2-256 hash function.
+const SHA2_256: u64 = 0x12;
+
+/// UnixFS node type of directories.
+const UNIXFS_DIRECTORY: u64 = 1;
+
+/// UnixFS node type of files.
+const UNIXFS_FILE: u64 = 2;
+
+/// Content identifier (CIDv1).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cid(Vec<u8>);
+
+impl Cid {
+    /// Computes CID of the block.
+    fn new(codec: u64, data: &[u8]) -> Self {
+        let mut bytes = vec![];
+        write_varint(&mut bytes, 1);
+        write_varint(&mut bytes, codec);
+        write_varint(&mut bytes, SHA2_256);
+        let digest = Sha256::digest(data);
+        write_varint(&mut bytes, digest.len() as u64);
+        bytes.extend_from_slice(&digest);
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for Cid {
+    /// Formats CID as multibase lowercase base32 string.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "b{}", base32(&self.0))
+    }
+}
+
+/// Imported DAG.
+#[derive(Clone, Debug)]
+pub struct Dag {
+    /// CID of the root node.
+    pub cid: Cid,
+
+    /// Total size of all blocks of the DAG in bytes.
+    pub size: u64,
+
+    /// Size of the imported content in bytes.
+    pub content_size: u64,
+}
+
+/// Imports the file or directory and returns its root CID and sizes.
+///
+/// Hidden files (names starting with `.`) in directories are skipped unless `hidden` is set.
+/// Symbolic links are followed.
+pub fn import(path: &Path, hidden: bool) -> io::Result<Dag> {
+    Importer { hidden, car: None }.import(path)
+}
+
+/// Imports the file or directory and writes all blocks of its DAG as CARv1 archive.
+pub fn write_car(path: &Path, hidden: bool, out: &mut dyn Write) -> io::Result<Dag> {
+    let dag = import(path, hidden)?;
+    let header = car_header(&dag.cid);
+    let mut prefix = vec![];
+    write_varint(&mut prefix, header.len() as u64);
+    out.write_all(&prefix)?;
+    out.write_all(&header)?;
+    Importer {
+        hidden,
+        car: Some(&mut *out),
+    }
+    .import(path)?;
+    out.flush()?;
+    Ok(dag)
+}
+
+/// Node of the DAG linked from its parent.
+struct Node {
+    cid: Cid,
+
+    /// Total size of the node and all its descendants.
+    tsize: u64,
+
+    /// Size of the content of the node.
+    filesize: u64,
+}
+
+/// Builder of DAG blocks.
+struct Importer<'a> {
+    hidden: bool,
+
+    /// Writer of CAR sections, if blocks are exported.
+    car: Option<&'a mut dyn Write>,
+}
+
+impl Importer<'_> {
+    fn import(&mut self, path: &Path) -> io::Result<Dag> {
+        let node = self.path(path)?;
+        Ok(Dag {
+            cid: node.cid,
+            size: node.tsize,
+            content_size: node.filesize,
+        })
+    }
+
+    fn path(&mut self, path: &Path) -> io::Result<Node> {
+        let metadata = std::fs::metadata(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+        if metadata.is_dir() {
+            self.directory(path)
+        } else if metadata.is_file() {
+            self.file(path)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}: not a regular file or directory", path.display()),
+            ))
+        }
+    }
+
+    /// Stores the block and returns its CID.
+    fn put(&mut self, codec: u64, data: &[u8]) -> io::Result<Cid> {
+        let cid = Cid::new(codec, data);
+        if let Some(car) = self.car.as_mut() {
+            let mut prefix = vec![];
+            write_varint(&mut prefix, (cid.0.len() + data.len()) as u64);
+            car.write_all(&prefix)?;
+            car.write_all(&cid.0)?;
+            car.write_all(data)?;
+        }
+        Ok(cid)
+    }
+
+    fn file(&mut self, path: &Path) -> io::Result<Node> {
+        let mut file = File::open(path)?;
+        let mut level = vec![];
+        loop {
+            let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+            (&mut file)
+                .take(CHUNK_SIZE as u64)
+                .read_to_end(&mut chunk)?;
+            // Empty file is a single empty leaf
+            if chunk.is_empty() && !level.is_empty() {
+                break;
+            }
+            let size = chunk.len() as u64;
+            level.push(Node {
+                cid: self.put(RAW, &chunk)?,
+                tsize: size,
+                filesize: size,
+            });
+            if chunk.len() < CHUNK_SIZE {
+                break;
+            }
+        }
+        while level.len() > 1 {
+            level = level
+                .chunks(MAX_LINKS)
+                .map(|children| self.file_node(children))
+                .collect::<io::Result<_>>()?;
+        }
+        Ok(level.remove(0))
+    }
+
+    fn file_node(&mut self, children: &[Node]) -> io::Result<Node> {
+        let filesize = children.iter().map(|child| child.filesize).sum();
+        let mut data = vec![];
+        write_uint_field(&mut data, 1, UNIXFS_FILE);
+        write_uint_field(&mut data, 3, filesize);
+        for child in children {
+            write_uint_field(&mut data, 4, child.filesize);
+        }
+        let links = children.iter().map(|child| ("", child)).collect::<Vec<_>>();
+        let block = pb_node(&links, &data);
+        Ok(Node {
+            cid: self.put(DAG_PB, &block)?,
+            tsize: block.len() as u64 + children.iter().map(|child| child.tsize).sum::<u64>(),
+            filesize,
+        })
+    }
+
+    fn directory(&mut self, path: &Path) -> io::Result<Node> {
+        let mut names = vec![];
+        for entry in std::fs::read_dir(path)? {
+            let name = entry?.file_name().into_string().map_err(|name| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: invalid file name {:?}", path.display(), name),
+                )
+            })?;
+            if self.hidden || !name.starts_with('.') {
+                names.push(name);
+            }
+        }
+        names.sort();
+
+        let mut children = vec![];
+        for name in names {
+            let child = self.path(&path.join(&name))?;
+            children.push((name, child));
+        }
+        let mut data = vec![];
+        write_uint_field(&mut data, 1, UNIXFS_DIRECTORY);
+        let links = children
+            .iter()
+            .map(|(name, child)| (name.as_str(), child))
+            .collect::<Vec<_>>();
+        let block = pb_node(&links, &data);
+        Ok(Node {
+            cid: self.put(DAG_PB, &block)?,
+            tsize: block.len() as u64 + children.iter().map(|(_, child)| child.tsize).sum::<u64>(),
+            filesize: children.iter().map(|(_, child)| child.filesize).sum(),
+        })
+    }
+}
+
+/// Encodes dag-pb node. Links are encoded before data as required by the dag-pb spec.
+fn pb_node(links: &[(&str, &Node)], data: &[u8]) -> Vec<u8> {
+    let mut node = vec![];
+    for (name, child) in links {
+        let mut link = vec![];
+        write_bytes_field(&mut link, 1, &child.cid.0);
+        write_bytes_field(&mut link, 2, name.as_bytes());
+        write_uint_field(&mut link, 3, child.tsize);
+        write_bytes_field(&mut node, 2, &link);
+    }
+    write_bytes_field(&mut node, 1, data);
+    node
+}
+
+/// Encodes dag-cbor header of CARv1 archive: `{"roots": [cid], "version": 1}`.
+fn car_header(root: &Cid) -> Vec<u8> {
+    let mut header = vec![0xa2, 0x65];
+    header.extend_from_slice(b"roots");
+    // Array of one CID: tag 42 with bytes prefixed by multibase identity
+    header.extend_from_slice(&[0x81, 0xd8, 0x2a]);
+    let length = root.0.len() + 1;
+    if length < 24 {
+        header.push(0x40 | length as u8);
+    } else {
+        header.extend_from_slice(&[0x58, length as u8]);
+    }
+    header.push(0x00);
+    header.extend_from_slice(&root.0);
+    header.push(0x67);
+    header.extend_from_slice(b"version");
+    header.push(0x01);
+    header
+}
+
+/// Writes unsigned LEB128 varint.
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Writes protobuf varint field.
+fn write_uint_field(out: &mut Vec<u8>, field: u64, value: u64) {
+    write_varint(out, field << 3);
+    write_varint(out, value);
+}
+
+/// Writes protobuf length-delimited field.
+fn write_bytes_field(out: &mut Vec<u8>, field: u64, value: &[u8]) {
+    write_varint(out, (field << 3) | 2);
+    write_varint(out, value.len() as u64);
+    out.extend_from_slice(value);
+}
+
+/// Encodes bytes as lowercase RFC 4648 base32 without padding.
+fn base32(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[(buffer >> bits) as usize & 31] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cid() {
+        assert_eq!(
+            Cid::new(RAW, b"").to_string(),
+            "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"
+        );
+        // Empty UnixFS directory
+        assert_eq!(
+            Cid::new(DAG_PB, &pb_node(&[], &[0x08, 0x01])).to_string(),
+            "bafybeiczsscdsbs7ffqz55asqdf3smv6klcw3gofszvwlyarci47bgf354"
+        );
+    }
+
+    #[test]
+    fn test_varint() {
+        let mut out = vec![];
+        write_varint(&mut out, 1);
+        write_varint(&mut out, 300);
+        assert_eq!(out, [0x01, 0xac, 0x02]);
+    }
+}
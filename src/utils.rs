@@ -5,21 +5,40 @@ use gevulot_rs::gevulot_client::GevulotClientBuilder;
 use gevulot_rs::GevulotClient;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::Value;
 use std::fmt;
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::os::fd::AsFd;
 use std::path::Path;
 
 use crate::commands::ChainArgs;
+use crate::query::{self, Query};
+use crate::table;
+
+/// Endpoint the Gevulot client connects to when none is configured.
+const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:9090";
+
+/// Returns the gRPC endpoint of the chain for queries of Cosmos SDK modules
+/// which are not exposed by the Gevulot client.
+///
+/// `chain_args` must be already resolved.
+pub fn grpc_endpoint(chain_args: &ChainArgs) -> String {
+    chain_args
+        .endpoint
+        .clone()
+        .unwrap_or(DEFAULT_ENDPOINT.to_string())
+}
 
 /// Connects to the Gevulot network using the provided command-line arguments.
 ///
 /// This function creates a GevulotClient based on the endpoint, gas price,
 /// gas multiplier, and mnemonic provided in the command-line arguments.
+/// Settings missing from command-line arguments are taken from the configuration profile.
 ///
 /// # Arguments
 ///
-/// * `matches` - A reference to the ArgMatches struct containing parsed command-line arguments.
+/// * `chain_args` - Chain arguments parsed from command-line arguments.
 ///
 /// # Returns
 ///
@@ -27,6 +46,7 @@ use crate::commands::ChainArgs;
 pub async fn connect_to_gevulot(
     chain_args: &ChainArgs,
 ) -> Result<GevulotClient, Box<dyn std::error::Error>> {
+    let chain_args = &chain_args.resolve()?;
     let mut client_builder = GevulotClientBuilder::default();
 
     // Set the endpoint if provided
@@ -90,6 +110,13 @@ pub async fn connect_to_gevulot(
 pub async fn read_file<T: DeserializeOwned>(
     path: Option<&Path>,
 ) -> Result<T, Box<dyn std::error::Error>> {
+    let content = read_input(path).await?;
+    let parsed: T = serde_yaml::from_str(&content)?;
+    Ok(parsed)
+}
+
+/// Reads the file or stdin into a string.
+pub async fn read_input(path: Option<&Path>) -> Result<String, Box<dyn std::error::Error>> {
     let content = match path {
         Some(file) => {
             let mut file = File::open(file)?;
@@ -103,8 +130,163 @@ pub async fn read_file<T: DeserializeOwned>(
             contents
         }
     };
-    let parsed: T = serde_yaml::from_str(&content)?;
-    Ok(parsed)
+    Ok(content)
+}
+
+/// YAML document read from a file.
+#[derive(Clone, Debug)]
+pub struct Document {
+    /// File the document was read from or `-` for stdin.
+    pub file: String,
+
+    /// Line number of the document start in the file.
+    pub line: usize,
+
+    /// Content of the document.
+    pub content: String,
+}
+
+/// Reads all YAML documents from the file, directory or stdin.
+///
+/// Documents in a file are separated with `---`. Files in a directory are read
+/// in alphabetical order, only files with `.yaml`, `.yml` or `.json` extension are read.
+pub async fn read_documents(
+    path: Option<&Path>,
+) -> Result<Vec<Document>, Box<dyn std::error::Error>> {
+    let files = match path {
+        Some(dir) if dir.is_dir() => {
+            let mut files = std::fs::read_dir(dir)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            files.retain(|file| {
+                file.is_file()
+                    && file
+                        .extension()
+                        .is_some_and(|ext| ext == "yaml" || ext == "yml" || ext == "json")
+            });
+            files.sort();
+            files.into_iter().map(Some).collect()
+        }
+        path => vec![path.map(Path::to_path_buf)],
+    };
+
+    let mut documents = vec![];
+    for file in files {
+        let content = read_input(file.as_deref()).await?;
+        let name = file
+            .map(|file| file.display().to_string())
+            .unwrap_or("-".to_string());
+        documents.extend(split_documents(&name, &content));
+    }
+    Ok(documents)
+}
+
+/// Splits multi-document YAML into separate documents. Empty documents are skipped.
+pub fn split_documents(file: &str, content: &str) -> Vec<Document> {
+    let mut documents = vec![];
+    let mut current = String::new();
+    let mut start = 1;
+    let mut push = |current: &mut String, start: usize| {
+        let is_empty = current.lines().all(|line| {
+            let line = line.trim();
+            line.is_empty() || line.starts_with('#')
+        });
+        if !is_empty {
+            documents.push(Document {
+                file: file.to_string(),
+                line: start,
+                content: std::mem::take(current),
+            });
+        }
+        current.clear();
+    };
+
+    for (i, line) in content.lines().enumerate() {
+        let is_separator = line
+            .strip_prefix("---")
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']));
+        if is_separator {
+            push(&mut current, start);
+            start = i + 2;
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    push(&mut current, start);
+    documents
+}
+
+/// Prompts for a secret on stderr and reads it from the terminal without echo.
+///
+/// If stdin is not a terminal, e.g. it is used for input files, the secret is read from `/dev/tty`.
+/// Fails if there is no terminal at all.
+pub fn read_secret(prompt: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        return read_secret_from(prompt, &stdin, stdin.lock());
+    }
+    let tty = File::options()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(|_| {
+            "can't prompt for a secret, stdin is not a terminal and there is no terminal"
+        })?;
+    read_secret_from(prompt, &tty, io::BufReader::new(&tty))
+}
+
+/// Reads a line from the terminal `fd` with echo disabled.
+fn read_secret_from(
+    prompt: &str,
+    fd: impl AsFd,
+    mut reader: impl BufRead,
+) -> Result<String, Box<dyn std::error::Error>> {
+    use nix::sys::termios::{tcgetattr, tcsetattr, LocalFlags, SetArg};
+
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+    let original = tcgetattr(&fd)?;
+    let mut silent = original.clone();
+    silent.local_flags.remove(LocalFlags::ECHO);
+    silent.local_flags.insert(LocalFlags::ECHONL);
+    tcsetattr(&fd, SetArg::TCSANOW, &silent)?;
+    let mut line = String::new();
+    let result = reader.read_line(&mut line);
+    tcsetattr(&fd, SetArg::TCSANOW, &original)?;
+    result?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Asks user for confirmation on stderr. Returns `true` if user answered yes.
+///
+/// Fails if stdin is not a terminal, because there is no one to answer.
+pub fn confirm(prompt: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let stdin = io::stdin();
+    if !stdin.is_terminal() {
+        return Err("confirmation required, but stdin is not a terminal (use --yes)".into());
+    }
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Returns the field of JSON value by its dot-separated path.
+///
+/// Array elements are addressed by their index, e.g. `metadata.labels.0.key`.
+pub fn get_field<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(array) => array.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
 }
 
 /// Possible output formats for Gevulot Control.
@@ -115,6 +297,8 @@ pub enum OutputFormat {
     Json,
     PrettyJson,
     Toml,
+    Table,
+    Wide,
 }
 
 impl Default for OutputFormat {
@@ -135,25 +319,82 @@ impl fmt::Display for OutputFormat {
     }
 }
 
+/// Output options for Gevulot Control.
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct OutputArgs {
+    /// Sets the output format.
+    #[arg(
+        global = true,
+        short = 'F',
+        long,
+        default_value_t,
+        env = "GEVULOT_FORMAT"
+    )]
+    pub format: OutputFormat,
+
+    /// Comma-separated list of fields to show as columns in table output.
+    ///
+    /// Fields are dot-separated paths in JSON representation of the object,
+    /// e.g. metadata.id,status.state.
+    #[arg(global = true, long, value_name = "FIELDS", value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Selects fields from the output before printing it.
+    ///
+    /// Supports a subset of jq and JSONPath syntax, e.g. .task_id, .[].metadata.id
+    /// or $[*].status.state.
+    #[arg(global = true, long, value_name = "EXPR", value_parser = Query::parse)]
+    pub query: Option<Query>,
+
+    /// Prints strings without quotes and arrays of scalars one per line.
+    ///
+    /// Other values are printed in the selected output format.
+    #[arg(global = true, long)]
+    pub raw: bool,
+}
+
 /// Prints an object in the specified format.
 ///
-/// This function takes a reference to command-line arguments and a serializable value,
-/// and prints the value in the format specified by the user (yaml, json, prettyjson, or toml).
+/// This function takes output options and a serializable value,
+/// applies the query if one is provided, and prints the value
+/// in the format specified by the user (yaml, json, prettyjson, toml, table or wide).
 ///
 /// # Arguments
 ///
-/// * `matches` - A reference to the ArgMatches struct containing parsed command-line arguments.
+/// * `output` - Output options parsed from command-line arguments.
 /// * `value` - A reference to the value to be printed, which must implement Serialize.
 ///
 /// # Returns
 ///
 /// A Result indicating success or an error if serialization or printing fails.
 pub fn print_object<T: Serialize>(
-    format: OutputFormat,
+    output: &OutputArgs,
+    value: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if output.query.is_none() && !output.raw {
+        return print_formatted(output, value);
+    }
+
+    let mut value = serde_json::to_value(value)?;
+    if let Some(query) = &output.query {
+        value = query.apply(&value);
+    }
+    if output.raw {
+        if let Some(raw) = query::raw_string(&value) {
+            println!("{}", raw);
+            return Ok(());
+        }
+    }
+    print_formatted(output, &value)
+}
+
+/// Serializes the value into the selected output format and prints it.
+fn print_formatted<T: Serialize>(
+    output: &OutputArgs,
     value: &T,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Match on the format string and serialize/print accordingly
-    match format {
+    match output.format {
         OutputFormat::Yaml => {
             // Serialize to YAML and print
             let yaml = serde_yaml::to_string(value)?;
@@ -174,6 +415,28 @@ pub fn print_object<T: Serialize>(
             let toml = toml::to_string(value)?;
             println!("{}", toml);
         }
+        OutputFormat::Table | OutputFormat::Wide => {
+            // Render JSON representation as a table and print
+            let value = serde_json::to_value(value)?;
+            let wide = output.format == OutputFormat::Wide;
+            print!("{}", table::render(&value, &output.columns, wide));
+        }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::split_documents;
+
+    #[test]
+    fn test_split_documents() {
+        let content = "kind: Task\n---\n# comment only\n--- # next\nkind: Pin\nversion: v0\n---\n";
+        let documents = split_documents("file.yaml", content);
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].line, 1);
+        assert_eq!(documents[0].content, "kind: Task\n");
+        assert_eq!(documents[1].line, 5);
+        assert_eq!(documents[1].content, "kind: Pin\nversion: v0\n");
+    }
+}